@@ -1,20 +1,19 @@
+use std::time::Duration;
+
 use buongiornissimo_rs::{
-    Augurando, BuongiornissimoCaffe, BuongiornoImmagini, Scrape, TiCondivido,
+    Augurando, BuongiornissimoCaffe, BuongiornoImmagini, GreetingPolicy, Scrape, ScrapeRandom,
+    TiCondivido,
 };
 use chrono::Local;
 use rand::Rng;
+use reqwest::Client;
 
-fn choice<T>(choices: &[T]) -> &T {
-    let mut rng = rand::rng();
-    &choices[rng.random_range(0..choices.len())]
-}
-
-fn get_provider() -> Box<dyn Scrape> {
-    match *choice(&[0, 1, 2, 3]) {
-        0 => Box::new(BuongiornissimoCaffe::default()),
-        1 => Box::new(BuongiornoImmagini::default()),
-        2 => Box::new(TiCondivido::default()),
-        3 => Box::new(Augurando::default()),
+fn get_provider(client: Client) -> Box<dyn Scrape> {
+    match rand::rng().random_range(0..4) {
+        0 => Box::new(BuongiornissimoCaffe::default().with_client(client)),
+        1 => Box::new(BuongiornoImmagini::default().with_client(client)),
+        2 => Box::new(TiCondivido::default().with_client(client)),
+        3 => Box::new(Augurando::default().with_client(client)),
         _ => panic!("out of range"),
     }
 }
@@ -22,9 +21,12 @@ fn get_provider() -> Box<dyn Scrape> {
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     tracing_subscriber::fmt::init();
-    let motd = buongiornissimo_rs::greeting_of_the_day(Local::now().date_naive(), true);
-    let urls = get_provider().scrape(motd).await?;
-    let url = choice(&urls);
+    let motd = buongiornissimo_rs::greeting_of_the_day(
+        Local::now().date_naive(),
+        &GreetingPolicy::default(),
+    );
+    let client = Client::builder().timeout(Duration::from_secs(30)).build()?;
+    let url = get_provider(client).scrape_random(motd).await?;
     open::that(url.to_string())?;
     Ok(())
 }
@@ -0,0 +1,11 @@
+//! # Aggregators
+//!
+//! This module exposes the scrapers which combine different providers together
+
+use super::{Greeting, Scrape, ScrapeError, ScrapeResult, ScrapedImage};
+
+mod fallback;
+mod merged;
+
+pub use self::fallback::Fallback;
+pub use self::merged::Merged;
@@ -0,0 +1,190 @@
+//! # Fallback
+//!
+//! A scraper which tries different providers in order
+
+use async_trait::async_trait;
+
+use super::{Greeting, Scrape, ScrapeError, ScrapeResult, ScrapedImage};
+
+/// A scraper which tries the wrapped providers in order and returns the first non-empty result.
+///
+/// Providers which don't support the requested greeting are skipped without being queried.
+/// If every provider fails, [`ScrapeError::AllProvidersFailed`] is returned with the error of each provider,
+/// in the same order of the providers.
+///
+/// ```rust
+/// use buongiornissimo_rs::{Augurando, BuongiornissimoCaffe, Fallback, TiCondivido};
+///
+/// let scraper = Fallback::default()
+///     .with_provider(BuongiornissimoCaffe::default())
+///     .with_provider(TiCondivido::default())
+///     .with_provider(Augurando::default());
+/// ```
+#[derive(Default)]
+pub struct Fallback {
+    providers: Vec<Box<dyn Scrape>>,
+}
+
+impl Fallback {
+    /// Create a new [`Fallback`] with the provided providers, which will be tried in order
+    pub fn new(providers: Vec<Box<dyn Scrape>>) -> Self {
+        Self { providers }
+    }
+
+    /// Append a provider to the providers to try
+    pub fn with_provider(mut self, provider: impl Scrape + 'static) -> Self {
+        self.providers.push(Box::new(provider));
+        self
+    }
+}
+
+#[async_trait]
+impl Scrape for Fallback {
+    async fn scrape_images(&self, greeting: Greeting) -> ScrapeResult<Vec<ScrapedImage>> {
+        let mut errors = Vec::with_capacity(self.providers.len());
+        for (i, provider) in self.providers.iter().enumerate() {
+            if !provider.supports(greeting) {
+                debug!("provider #{} doesn't support {:?}; skipping", i, greeting);
+                errors.push(ScrapeError::UnsupportedGreeting);
+                continue;
+            }
+            debug!(
+                "scraping greeting of kind {:?} with provider #{}",
+                greeting, i
+            );
+            match provider.scrape_images(greeting).await {
+                Ok(images) if images.is_empty() => {
+                    debug!("provider #{} returned no images", i);
+                    errors.push(ScrapeError::no_images_found());
+                }
+                Ok(images) => return Ok(images),
+                Err(err) => {
+                    debug!("provider #{} failed: {}", i, err);
+                    errors.push(err);
+                }
+            }
+        }
+
+        error!("all providers failed for greeting {:?}", greeting);
+        Err(ScrapeError::AllProvidersFailed(errors))
+    }
+
+    fn supports(&self, greeting: Greeting) -> bool {
+        self.providers
+            .iter()
+            .any(|provider| provider.supports(greeting))
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use std::sync::Arc;
+
+    use pretty_assertions::assert_eq;
+
+    use url::Url;
+
+    use super::*;
+    use crate::mock::MockProvider;
+
+    #[tokio::test]
+    async fn should_return_first_successful_result() {
+        let first = Arc::new(
+            MockProvider::default().with_urls(Greeting::BuonGiorno, &["https://a.it/1.jpg"]),
+        );
+        let second = Arc::new(
+            MockProvider::default().with_urls(Greeting::BuonGiorno, &["https://b.it/1.jpg"]),
+        );
+        let scraper = Fallback::default()
+            .with_provider(first.clone())
+            .with_provider(second.clone());
+
+        assert_eq!(
+            scraper.scrape(Greeting::BuonGiorno).await.unwrap(),
+            vec![Url::parse("https://a.it/1.jpg").unwrap()]
+        );
+        assert_eq!(first.calls(), 1);
+        assert_eq!(second.calls(), 0);
+    }
+
+    #[tokio::test]
+    async fn should_fallback_to_next_provider() {
+        let scraper = Fallback::new(vec![
+            Box::new(MockProvider::default()),
+            Box::new(MockProvider::default().with_urls(Greeting::BuonGiorno, &[])),
+            Box::new(
+                MockProvider::default().with_urls(Greeting::BuonGiorno, &["https://c.it/1.jpg"]),
+            ),
+        ]);
+
+        assert_eq!(
+            scraper.scrape(Greeting::BuonGiorno).await.unwrap(),
+            vec![Url::parse("https://c.it/1.jpg").unwrap()]
+        );
+    }
+
+    #[tokio::test]
+    async fn should_return_errors_of_all_providers() {
+        let scraper = Fallback::default()
+            .with_provider(MockProvider::default())
+            .with_provider(
+                MockProvider::default()
+                    .with_error(Greeting::BuonGiorno, ScrapeError::no_images_found()),
+            );
+
+        let err = scraper.scrape(Greeting::BuonGiorno).await.unwrap_err();
+        assert_eq!(
+            err,
+            ScrapeError::AllProvidersFailed(vec![
+                ScrapeError::UnsupportedGreeting,
+                ScrapeError::no_images_found()
+            ])
+        );
+        assert_eq!(
+            err.to_string(),
+            "all providers failed: [this scraper doesn't support this greeting type; could not find any image]"
+        );
+    }
+
+    #[tokio::test]
+    async fn should_not_query_providers_not_supporting_greeting() {
+        let first = Arc::new(MockProvider::default());
+        let second = Arc::new(
+            MockProvider::default().with_urls(Greeting::BuonGiorno, &["https://b.it/1.jpg"]),
+        );
+        let scraper = Fallback::default()
+            .with_provider(first.clone())
+            .with_provider(second.clone());
+
+        assert!(scraper.scrape(Greeting::BuonGiorno).await.is_ok());
+        assert_eq!(first.calls(), 0);
+        assert_eq!(second.calls(), 1);
+    }
+
+    #[test]
+    fn should_support_greetings_supported_by_any_provider() {
+        let scraper = Fallback::default()
+            .with_provider(MockProvider::default().with_urls(Greeting::BuonGiorno, &[]))
+            .with_provider(MockProvider::default().with_urls(Greeting::Natale, &[]));
+
+        assert!(scraper.supports(Greeting::BuonGiorno));
+        assert!(scraper.supports(Greeting::Natale));
+        assert!(!scraper.supports(Greeting::Pasqua));
+        assert_eq!(
+            scraper.supported_greetings().collect::<Vec<_>>(),
+            vec![Greeting::BuonGiorno, Greeting::Natale]
+        );
+    }
+
+    #[tokio::test]
+    async fn should_fail_without_providers() {
+        assert_eq!(
+            Fallback::default()
+                .scrape(Greeting::BuonGiorno)
+                .await
+                .unwrap_err(),
+            ScrapeError::AllProvidersFailed(vec![])
+        );
+    }
+}
@@ -0,0 +1,264 @@
+//! # Merged
+//!
+//! A scraper which queries different providers concurrently and merges their results
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures_util::future::join_all;
+
+use super::{Greeting, Scrape, ScrapeError, ScrapeResult, ScrapedImage};
+
+/// A scraper which queries all the wrapped providers concurrently for the same greeting,
+/// and returns the merged list of the images, without duplicated urls.
+///
+/// Images are returned in the order of the providers. A failing provider doesn't prevent the others from being queried,
+/// providers which don't support the greeting are skipped without sending any request,
+/// and if a timeout is set, providers which don't answer in time are dropped.
+/// The scrape succeeds as long as at least one provider returned some images, otherwise
+/// [`ScrapeError::AllProvidersFailed`] is returned with the error of each provider.
+///
+/// ```rust
+/// use std::time::Duration;
+///
+/// use buongiornissimo_rs::{Augurando, BuongiornoImmagini, Merged, TiCondivido};
+///
+/// let scraper = Merged::default()
+///     .with_provider(BuongiornoImmagini::default())
+///     .with_provider(TiCondivido::default())
+///     .with_provider(Augurando::default())
+///     .with_timeout(Duration::from_secs(10));
+/// ```
+#[derive(Default)]
+pub struct Merged {
+    providers: Vec<Box<dyn Scrape>>,
+    timeout: Option<Duration>,
+}
+
+impl Merged {
+    /// Create a new [`Merged`] with the provided providers
+    pub fn new(providers: Vec<Box<dyn Scrape>>) -> Self {
+        Self {
+            providers,
+            timeout: None,
+        }
+    }
+
+    /// Append a provider to the providers to query
+    pub fn with_provider(mut self, provider: impl Scrape + 'static) -> Self {
+        self.providers.push(Box::new(provider));
+        self
+    }
+
+    /// Set the maximum time to wait for each provider. Providers which don't answer in time are dropped.
+    ///
+    /// By default there is no timeout.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    async fn scrape_provider(
+        &self,
+        provider: &dyn Scrape,
+        greeting: Greeting,
+    ) -> ScrapeResult<Vec<ScrapedImage>> {
+        if !provider.supports(greeting) {
+            return Err(ScrapeError::UnsupportedGreeting);
+        }
+        match self.timeout {
+            Some(timeout) => tokio::time::timeout(timeout, provider.scrape_images(greeting))
+                .await
+                .unwrap_or(Err(ScrapeError::Timeout)),
+            None => provider.scrape_images(greeting).await,
+        }
+    }
+}
+
+#[async_trait]
+impl Scrape for Merged {
+    async fn scrape_images(&self, greeting: Greeting) -> ScrapeResult<Vec<ScrapedImage>> {
+        debug!(
+            "scraping greeting of kind {:?} with {} providers",
+            greeting,
+            self.providers.len()
+        );
+        let results = join_all(
+            self.providers
+                .iter()
+                .map(|provider| self.scrape_provider(provider.as_ref(), greeting)),
+        )
+        .await;
+
+        let mut seen = HashSet::new();
+        let mut images = Vec::new();
+        let mut errors = Vec::new();
+        for (i, result) in results.into_iter().enumerate() {
+            match result {
+                Ok(provider_images) if provider_images.is_empty() => {
+                    debug!("provider #{} returned no images", i);
+                    errors.push(ScrapeError::no_images_found());
+                }
+                Ok(provider_images) => {
+                    debug!("provider #{} returned {} images", i, provider_images.len());
+                    images.extend(
+                        provider_images
+                            .into_iter()
+                            .filter(|image| seen.insert(image.url.clone())),
+                    );
+                }
+                Err(err) => {
+                    debug!("provider #{} failed: {}", i, err);
+                    errors.push(err);
+                }
+            }
+        }
+
+        if images.is_empty() {
+            error!("all providers failed for greeting {:?}", greeting);
+            return Err(ScrapeError::AllProvidersFailed(errors));
+        }
+        Ok(images)
+    }
+
+    fn supports(&self, greeting: Greeting) -> bool {
+        self.providers
+            .iter()
+            .any(|provider| provider.supports(greeting))
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use std::sync::Arc;
+
+    use pretty_assertions::assert_eq;
+
+    use url::Url;
+
+    use super::*;
+    use crate::mock::MockProvider;
+
+    #[tokio::test]
+    async fn should_merge_and_deduplicate_results() {
+        let scraper = Merged::default()
+            .with_provider(MockProvider::default().with_urls(
+                Greeting::BuonGiorno,
+                &["https://a.it/1.jpg", "https://a.it/2.jpg"],
+            ))
+            .with_provider(MockProvider::default().with_urls(
+                Greeting::BuonGiorno,
+                &["https://a.it/2.jpg", "https://b.it/1.jpg"],
+            ));
+
+        assert_eq!(
+            scraper.scrape(Greeting::BuonGiorno).await.unwrap(),
+            vec![
+                Url::parse("https://a.it/1.jpg").unwrap(),
+                Url::parse("https://a.it/2.jpg").unwrap(),
+                Url::parse("https://b.it/1.jpg").unwrap(),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn should_ignore_failing_providers() {
+        let scraper = Merged::new(vec![
+            Box::new(MockProvider::default()),
+            Box::new(
+                MockProvider::default().with_urls(Greeting::BuonGiorno, &["https://b.it/1.jpg"]),
+            ),
+        ]);
+
+        assert_eq!(
+            scraper.scrape(Greeting::BuonGiorno).await.unwrap(),
+            vec![Url::parse("https://b.it/1.jpg").unwrap()]
+        );
+    }
+
+    #[tokio::test]
+    async fn should_drop_providers_exceeding_timeout() {
+        let scraper = Merged::default()
+            .with_provider(
+                MockProvider::default()
+                    .with_urls(Greeting::BuonGiorno, &["https://a.it/1.jpg"])
+                    .with_delay(Duration::from_secs(30)),
+            )
+            .with_provider(
+                MockProvider::default().with_urls(Greeting::BuonGiorno, &["https://b.it/1.jpg"]),
+            )
+            .with_timeout(Duration::from_millis(100));
+
+        assert_eq!(
+            scraper.scrape(Greeting::BuonGiorno).await.unwrap(),
+            vec![Url::parse("https://b.it/1.jpg").unwrap()]
+        );
+    }
+
+    #[tokio::test]
+    async fn should_query_providers_concurrently() {
+        let scraper = Merged::default()
+            .with_provider(
+                MockProvider::default()
+                    .with_urls(Greeting::BuonGiorno, &["https://a.it/1.jpg"])
+                    .with_delay(Duration::from_millis(300)),
+            )
+            .with_provider(
+                MockProvider::default()
+                    .with_urls(Greeting::BuonGiorno, &["https://b.it/1.jpg"])
+                    .with_delay(Duration::from_millis(300)),
+            );
+
+        let started = std::time::Instant::now();
+        assert_eq!(scraper.scrape(Greeting::BuonGiorno).await.unwrap().len(), 2);
+        assert!(started.elapsed() < Duration::from_millis(550));
+    }
+
+    #[tokio::test]
+    async fn should_not_query_providers_not_supporting_greeting() {
+        let first = Arc::new(MockProvider::default());
+        let second = Arc::new(
+            MockProvider::default().with_urls(Greeting::BuonGiorno, &["https://b.it/1.jpg"]),
+        );
+        let scraper = Merged::default()
+            .with_provider(first.clone())
+            .with_provider(second.clone());
+
+        assert!(scraper.scrape(Greeting::BuonGiorno).await.is_ok());
+        assert_eq!(first.calls(), 0);
+        assert_eq!(second.calls(), 1);
+    }
+
+    #[test]
+    fn should_support_greetings_supported_by_any_provider() {
+        let scraper = Merged::default()
+            .with_provider(MockProvider::default().with_urls(Greeting::BuonGiorno, &[]))
+            .with_provider(MockProvider::default().with_urls(Greeting::Natale, &[]));
+
+        assert!(scraper.supports(Greeting::BuonGiorno));
+        assert!(scraper.supports(Greeting::Natale));
+        assert!(!scraper.supports(Greeting::Pasqua));
+    }
+
+    #[tokio::test]
+    async fn should_fail_if_all_providers_fail() {
+        let scraper = Merged::default()
+            .with_provider(MockProvider::default())
+            .with_provider(
+                MockProvider::default()
+                    .with_urls(Greeting::BuonGiorno, &["https://b.it/1.jpg"])
+                    .with_delay(Duration::from_secs(30)),
+            )
+            .with_timeout(Duration::from_millis(100));
+
+        assert_eq!(
+            scraper.scrape(Greeting::BuonGiorno).await.unwrap_err(),
+            ScrapeError::AllProvidersFailed(vec![
+                ScrapeError::UnsupportedGreeting,
+                ScrapeError::Timeout
+            ])
+        );
+    }
+}
@@ -0,0 +1,11 @@
+//! # Cache
+//!
+//! This module exposes the scrapers which cache the results of other scrapers
+
+use super::{Greeting, Scrape, ScrapeResult, ScrapedImage};
+
+mod disk;
+mod memory;
+
+pub use self::disk::DiskCache;
+pub use self::memory::MemoryCache;
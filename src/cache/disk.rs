@@ -0,0 +1,474 @@
+//! # Disk cache
+//!
+//! A scraper which persists on disk the results of another scraper
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use url::Url;
+
+use super::{Greeting, Scrape, ScrapeResult, ScrapedImage};
+use crate::fs;
+
+const DEFAULT_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+const HEADER: &str = "# buongiornissimo-rs cache v1";
+
+/// The images persisted for a greeting, with the time they have been scraped at
+#[derive(Debug, Clone, PartialEq)]
+struct Snapshot {
+    fetched_at: SystemTime,
+    images: Vec<ScrapedImage>,
+}
+
+/// A scraper which persists on disk the images scraped by the wrapped provider for each [`Greeting`],
+/// so that they survive restarts.
+///
+/// Each greeting is stored in its own file, under a directory named after the provider,
+/// together with the time it has been scraped at. Only the urls of the images, of their pages and of their provider are persisted.
+///
+/// - Images younger than the TTL (24 hours by default) are returned without querying the provider.
+/// - Older images are scraped again; if the provider fails, the last persisted images are returned instead of the error.
+/// - In stale-while-revalidate mode, older images are returned immediately, while they're scraped again in the background.
+///
+/// ```rust
+/// use std::time::Duration;
+///
+/// use buongiornissimo_rs::{Augurando, DiskCache};
+///
+/// let scraper = DiskCache::new("augurando", Augurando::default(), "/var/cache/buongiornissimo")
+///     .with_ttl(Duration::from_secs(12 * 60 * 60))
+///     .with_stale_while_revalidate(true);
+/// ```
+pub struct DiskCache<S> {
+    provider: Arc<S>,
+    directory: PathBuf,
+    ttl: Duration,
+    stale_while_revalidate: bool,
+    refreshing: Arc<Mutex<HashSet<Greeting>>>,
+}
+
+impl<S> DiskCache<S>
+where
+    S: Scrape + 'static,
+{
+    /// Create a new [`DiskCache`] wrapping `provider`, which stores the images in `directory`/`name`.
+    ///
+    /// `name` identifies the provider, so that different providers can share the same directory.
+    pub fn new(name: &str, provider: S, directory: impl AsRef<Path>) -> Self {
+        Self {
+            provider: Arc::new(provider),
+            directory: directory.as_ref().join(sanitize(name)),
+            ttl: DEFAULT_TTL,
+            stale_while_revalidate: false,
+            refreshing: Arc::default(),
+        }
+    }
+
+    /// Set for how long the persisted images are considered fresh
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// If enabled, expired images are returned immediately and scraped again in the background
+    pub fn with_stale_while_revalidate(mut self, stale_while_revalidate: bool) -> Self {
+        self.stale_while_revalidate = stale_while_revalidate;
+        self
+    }
+
+    /// Get the wrapped provider
+    pub fn provider(&self) -> &S {
+        &self.provider
+    }
+
+    /// Get the time the persisted images for `greeting` have been scraped at, if any
+    pub async fn fetched_at(&self, greeting: Greeting) -> Option<SystemTime> {
+        read(&self.path(greeting))
+            .await
+            .map(|snapshot| snapshot.fetched_at)
+    }
+
+    fn path(&self, greeting: Greeting) -> PathBuf {
+        self.directory.join(format!("{greeting}.cache"))
+    }
+
+    fn is_fresh(&self, snapshot: &Snapshot) -> bool {
+        snapshot
+            .fetched_at
+            .elapsed()
+            .is_ok_and(|elapsed| elapsed < self.ttl)
+    }
+
+    fn is_refreshing(&self, greeting: Greeting) -> bool {
+        self.refreshing
+            .lock()
+            .expect("cache lock poisoned")
+            .contains(&greeting)
+    }
+
+    /// Scrape `greeting` again in the background, unless it's already being refreshed
+    fn refresh_in_background(&self, greeting: Greeting) {
+        if !self
+            .refreshing
+            .lock()
+            .expect("cache lock poisoned")
+            .insert(greeting)
+        {
+            return;
+        }
+        let provider = self.provider.clone();
+        let refreshing = self.refreshing.clone();
+        let path = self.path(greeting);
+        tokio::spawn(async move {
+            debug!("refreshing {:?} in background", greeting);
+            match provider.scrape_images(greeting).await {
+                Ok(images) => write(&path, &images).await,
+                Err(err) => error!("failed to refresh {:?} in background: {}", greeting, err),
+            }
+            refreshing
+                .lock()
+                .expect("cache lock poisoned")
+                .remove(&greeting);
+        });
+    }
+}
+
+#[async_trait]
+impl<S> Scrape for DiskCache<S>
+where
+    S: Scrape + 'static,
+{
+    async fn scrape_images(&self, greeting: Greeting) -> ScrapeResult<Vec<ScrapedImage>> {
+        let path = self.path(greeting);
+        let snapshot = read(&path).await;
+        match snapshot {
+            Some(snapshot) if self.is_fresh(&snapshot) => {
+                debug!("found fresh {:?} in {}", greeting, path.display());
+                return Ok(snapshot.images);
+            }
+            Some(snapshot) if self.stale_while_revalidate => {
+                debug!("found stale {:?} in {}", greeting, path.display());
+                self.refresh_in_background(greeting);
+                return Ok(snapshot.images);
+            }
+            _ => {}
+        }
+
+        match self.provider.scrape_images(greeting).await {
+            Ok(images) => {
+                // a refresh in flight is going to persist the same greeting
+                if self.is_refreshing(greeting) {
+                    debug!(
+                        "{:?} is being refreshed in background; not persisting",
+                        greeting
+                    );
+                } else {
+                    write(&path, &images).await;
+                }
+                Ok(images)
+            }
+            Err(err) => match snapshot {
+                Some(snapshot) => {
+                    error!(
+                        "failed to scrape {:?}: {}; returning the images persisted in {}",
+                        greeting,
+                        err,
+                        path.display()
+                    );
+                    Ok(snapshot.images)
+                }
+                None => Err(err),
+            },
+        }
+    }
+
+    fn supports(&self, greeting: Greeting) -> bool {
+        self.provider.supports(greeting)
+    }
+}
+
+/// Make `name` safe to be used as a file name
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect::<String>()
+        .split('-')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Read the snapshot persisted at `path`. Missing and invalid files are considered as not cached.
+async fn read(path: &Path) -> Option<Snapshot> {
+    let content = tokio::fs::read_to_string(path).await.ok()?;
+    let snapshot = decode(&content);
+    if snapshot.is_none() {
+        error!("invalid cache file at {}", path.display());
+    }
+    snapshot
+}
+
+/// Persist `images` at `path`. Errors are logged, since the cache is not essential.
+async fn write(path: &Path, images: &[ScrapedImage]) {
+    let content = encode(&Snapshot {
+        fetched_at: SystemTime::now(),
+        images: images.to_vec(),
+    });
+    if let Err(err) = fs::write_atomic(path, content).await {
+        error!("failed to write cache file at {}: {}", path.display(), err);
+    }
+}
+
+/// Encode a snapshot: a header, the unix timestamp and a line for each image, with its url, page url and provider
+fn encode(snapshot: &Snapshot) -> String {
+    let timestamp = snapshot
+        .fetched_at
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let mut content = format!("{HEADER}\n{timestamp}\n");
+    for image in &snapshot.images {
+        content.push_str(&format!(
+            "{}\t{}\t{}\n",
+            image.url,
+            image.page_url,
+            image.provider.replace(['\t', '\n'], " ")
+        ));
+    }
+    content
+}
+
+fn decode(content: &str) -> Option<Snapshot> {
+    let mut lines = content.lines();
+    if lines.next()? != HEADER {
+        return None;
+    }
+    let timestamp = lines.next()?.trim().parse::<u64>().ok()?;
+    let images = lines
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let mut fields = line.split('\t');
+            let url = Url::parse(fields.next()?).ok()?;
+            let page_url = Url::parse(fields.next()?).ok()?;
+            let provider = fields.next()?;
+            Some(ScrapedImage::new(url, page_url, provider))
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    Some(Snapshot {
+        fetched_at: UNIX_EPOCH + Duration::from_secs(timestamp),
+        images,
+    })
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::ScrapeError;
+    use crate::mock::MockProvider;
+
+    fn cache(provider: MockProvider, directory: &TempDir) -> DiskCache<MockProvider> {
+        DiskCache::new("Mock", provider, directory.path())
+    }
+
+    fn urls(images: &[ScrapedImage]) -> Vec<&str> {
+        images.iter().map(|image| image.url.as_str()).collect()
+    }
+
+    /// Persist a snapshot for `greeting`, scraped `age` ago
+    async fn persist(directory: &TempDir, greeting: Greeting, urls: &[&str], age: Duration) {
+        let scraper = cache(MockProvider::default(), directory);
+        let page_url = Url::parse("https://mock.it/").unwrap();
+        let snapshot = Snapshot {
+            fetched_at: SystemTime::now() - age,
+            images: urls
+                .iter()
+                .map(|url| ScrapedImage::new(Url::parse(url).unwrap(), page_url.clone(), "Mock"))
+                .collect(),
+        };
+        let path = scraper.path(greeting);
+        tokio::fs::create_dir_all(path.parent().unwrap())
+            .await
+            .unwrap();
+        tokio::fs::write(path, encode(&snapshot)).await.unwrap();
+    }
+
+    #[test]
+    fn should_sanitize_names() {
+        assert_eq!(sanitize("BuongiornissimoCaffe"), "buongiornissimocaffe");
+        assert_eq!(sanitize("../../etc"), "etc");
+    }
+
+    #[test]
+    fn should_encode_and_decode_snapshot() {
+        let snapshot = Snapshot {
+            fetched_at: UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+            images: vec![ScrapedImage::new(
+                Url::parse("https://a.it/1.jpg").unwrap(),
+                Url::parse("https://a.it/buongiorno/").unwrap(),
+                "Mock",
+            )],
+        };
+        let content = encode(&snapshot);
+        assert_eq!(
+            content,
+            "# buongiornissimo-rs cache v1\n1700000000\nhttps://a.it/1.jpg\thttps://a.it/buongiorno/\tMock\n"
+        );
+        assert_eq!(decode(&content), Some(snapshot));
+        assert_eq!(decode("garbage"), None);
+        assert_eq!(
+            decode("# buongiornissimo-rs cache v1\n1700000000\nnot-a-url\n"),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn should_persist_images() {
+        let directory = TempDir::new().unwrap();
+        let scraper = cache(
+            MockProvider::default().with_urls(Greeting::BuonGiorno, &["https://a.it/1.jpg"]),
+            &directory,
+        );
+        assert!(scraper.fetched_at(Greeting::BuonGiorno).await.is_none());
+        scraper.scrape(Greeting::BuonGiorno).await.unwrap();
+        assert!(scraper.fetched_at(Greeting::BuonGiorno).await.is_some());
+
+        // a new instance, e.g. after a restart, reads the persisted images
+        let scraper = cache(
+            MockProvider::default().with_urls(Greeting::BuonGiorno, &["https://a.it/2.jpg"]),
+            &directory,
+        );
+        assert_eq!(
+            urls(&scraper.scrape_images(Greeting::BuonGiorno).await.unwrap()),
+            vec!["https://a.it/1.jpg"]
+        );
+        assert_eq!(scraper.provider().calls(), 0);
+        assert!(directory.path().join("mock/buongiorno.cache").exists());
+    }
+
+    #[tokio::test]
+    async fn should_name_files_after_greeting_slug() {
+        let directory = TempDir::new().unwrap();
+        let greeting = Greeting::BuonGiornoWeekday(chrono::Weekday::Mon);
+        let scraper = cache(
+            MockProvider::default().with_urls(greeting, &["https://a.it/1.jpg"]),
+            &directory,
+        );
+        scraper.scrape(greeting).await.unwrap();
+        assert_eq!(
+            scraper.path(greeting),
+            directory.path().join("mock/buon-lunedi.cache")
+        );
+        assert!(directory.path().join("mock/buon-lunedi.cache").exists());
+    }
+
+    #[tokio::test]
+    async fn should_scrape_again_expired_images() {
+        let directory = TempDir::new().unwrap();
+        persist(
+            &directory,
+            Greeting::BuonGiorno,
+            &["https://a.it/1.jpg"],
+            Duration::from_secs(3600),
+        )
+        .await;
+        let scraper = cache(
+            MockProvider::default().with_urls(Greeting::BuonGiorno, &["https://a.it/2.jpg"]),
+            &directory,
+        )
+        .with_ttl(Duration::from_secs(60));
+
+        assert_eq!(
+            urls(&scraper.scrape_images(Greeting::BuonGiorno).await.unwrap()),
+            vec!["https://a.it/2.jpg"]
+        );
+        assert_eq!(scraper.provider().calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn should_fallback_to_persisted_images_if_provider_fails() {
+        let directory = TempDir::new().unwrap();
+        persist(
+            &directory,
+            Greeting::BuonGiorno,
+            &["https://a.it/1.jpg"],
+            Duration::from_secs(3600),
+        )
+        .await;
+        let scraper = cache(
+            MockProvider::default()
+                .with_error(Greeting::BuonGiorno, ScrapeError::no_images_found()),
+            &directory,
+        )
+        .with_ttl(Duration::from_secs(60));
+
+        assert_eq!(
+            urls(&scraper.scrape_images(Greeting::BuonGiorno).await.unwrap()),
+            vec!["https://a.it/1.jpg"]
+        );
+        assert_eq!(scraper.provider().calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn should_return_error_without_persisted_images() {
+        let directory = TempDir::new().unwrap();
+        let scraper = cache(
+            MockProvider::default()
+                .with_error(Greeting::BuonGiorno, ScrapeError::no_images_found()),
+            &directory,
+        );
+
+        assert_eq!(
+            scraper.scrape(Greeting::BuonGiorno).await.unwrap_err(),
+            ScrapeError::no_images_found()
+        );
+    }
+
+    #[tokio::test]
+    async fn should_revalidate_stale_images_in_background() {
+        let directory = TempDir::new().unwrap();
+        persist(
+            &directory,
+            Greeting::BuonGiorno,
+            &["https://a.it/1.jpg"],
+            Duration::from_secs(3600),
+        )
+        .await;
+        let scraper = cache(
+            MockProvider::default()
+                .with_urls(Greeting::BuonGiorno, &["https://a.it/2.jpg"])
+                .with_delay(Duration::from_millis(50)),
+            &directory,
+        )
+        .with_ttl(Duration::from_secs(60))
+        .with_stale_while_revalidate(true);
+
+        // stale images are returned immediately, and refreshed once
+        for _ in 0..3 {
+            assert_eq!(
+                urls(&scraper.scrape_images(Greeting::BuonGiorno).await.unwrap()),
+                vec!["https://a.it/1.jpg"]
+            );
+        }
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(scraper.provider().calls(), 1);
+        assert_eq!(
+            urls(&scraper.scrape_images(Greeting::BuonGiorno).await.unwrap()),
+            vec!["https://a.it/2.jpg"]
+        );
+        assert_eq!(scraper.provider().calls(), 1);
+    }
+}
@@ -0,0 +1,313 @@
+//! # Memory cache
+//!
+//! A scraper which caches in memory the results of another scraper
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use futures_util::FutureExt;
+use futures_util::future::{BoxFuture, Shared};
+
+use super::{Greeting, Scrape, ScrapeResult, ScrapedImage};
+
+const DEFAULT_TTL: Duration = Duration::from_secs(60 * 60);
+const DEFAULT_CAPACITY: usize = 64;
+
+type InFlight = Shared<BoxFuture<'static, ScrapeResult<Vec<ScrapedImage>>>>;
+
+/// A cached result
+struct Entry {
+    images: Vec<ScrapedImage>,
+    fetched_at: Instant,
+    last_access: Instant,
+}
+
+/// A scraper which caches in memory the images scraped by the wrapped provider for each [`Greeting`].
+///
+/// Cached images are returned until they're older than the TTL (1 hour by default).
+/// When the cache is full (64 greetings by default), the least recently used greeting is evicted.
+/// Concurrent scrapes of the same greeting are coalesced, so that the provider is queried once, and errors are never cached.
+///
+/// ```rust
+/// use std::time::Duration;
+///
+/// use buongiornissimo_rs::{BuongiornissimoCaffe, MemoryCache};
+///
+/// let scraper = MemoryCache::new(BuongiornissimoCaffe::default())
+///     .with_ttl(Duration::from_secs(6 * 60 * 60))
+///     .with_capacity(16);
+/// ```
+pub struct MemoryCache<S> {
+    provider: Arc<S>,
+    ttl: Duration,
+    capacity: usize,
+    entries: Mutex<HashMap<Greeting, Entry>>,
+    in_flight: Mutex<HashMap<Greeting, InFlight>>,
+}
+
+impl<S> MemoryCache<S>
+where
+    S: Scrape + 'static,
+{
+    /// Create a new [`MemoryCache`] wrapping `provider`
+    pub fn new(provider: S) -> Self {
+        Self {
+            provider: Arc::new(provider),
+            ttl: DEFAULT_TTL,
+            capacity: DEFAULT_CAPACITY,
+            entries: Mutex::default(),
+            in_flight: Mutex::default(),
+        }
+    }
+
+    /// Set for how long the scraped images are cached
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Set the maximum amount of greetings to cache
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Get the wrapped provider
+    pub fn provider(&self) -> &S {
+        &self.provider
+    }
+
+    /// Remove the cached images for `greeting`
+    pub fn invalidate(&self, greeting: Greeting) {
+        self.entries().remove(&greeting);
+    }
+
+    /// Remove all the cached images
+    pub fn clear(&self) {
+        self.entries().clear();
+    }
+
+    fn entries(&self) -> std::sync::MutexGuard<'_, HashMap<Greeting, Entry>> {
+        self.entries.lock().expect("cache lock poisoned")
+    }
+
+    fn cached(&self, greeting: Greeting) -> Option<Vec<ScrapedImage>> {
+        let mut entries = self.entries();
+        let entry = entries.get_mut(&greeting)?;
+        if entry.fetched_at.elapsed() >= self.ttl {
+            return None;
+        }
+        entry.last_access = Instant::now();
+        Some(entry.images.clone())
+    }
+
+    fn store(&self, greeting: Greeting, images: Vec<ScrapedImage>) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut entries = self.entries();
+        if !entries.contains_key(&greeting) && entries.len() >= self.capacity {
+            let least_recently_used = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_access)
+                .map(|(greeting, _)| *greeting);
+            if let Some(evicted) = least_recently_used {
+                debug!("cache is full; evicting {:?}", evicted);
+                entries.remove(&evicted);
+            }
+        }
+        let now = Instant::now();
+        entries.insert(
+            greeting,
+            Entry {
+                images,
+                fetched_at: now,
+                last_access: now,
+            },
+        );
+    }
+
+    /// Get the in-flight scrape for `greeting`, or start a new one
+    fn in_flight(&self, greeting: Greeting) -> InFlight {
+        self.in_flight
+            .lock()
+            .expect("cache lock poisoned")
+            .entry(greeting)
+            .or_insert_with(|| {
+                let provider = self.provider.clone();
+                async move { provider.scrape_images(greeting).await }
+                    .boxed()
+                    .shared()
+            })
+            .clone()
+    }
+}
+
+#[async_trait]
+impl<S> Scrape for MemoryCache<S>
+where
+    S: Scrape + 'static,
+{
+    async fn scrape_images(&self, greeting: Greeting) -> ScrapeResult<Vec<ScrapedImage>> {
+        if let Some(images) = self.cached(greeting) {
+            debug!("found {:?} in cache", greeting);
+            return Ok(images);
+        }
+
+        let scrape = self.in_flight(greeting);
+        let result = scrape.clone().await;
+        {
+            let mut in_flight = self.in_flight.lock().expect("cache lock poisoned");
+            if in_flight
+                .get(&greeting)
+                .is_some_and(|current| current.ptr_eq(&scrape))
+            {
+                in_flight.remove(&greeting);
+                if let Ok(images) = &result {
+                    self.store(greeting, images.clone());
+                }
+            }
+        }
+
+        result
+    }
+
+    fn supports(&self, greeting: Greeting) -> bool {
+        self.provider.supports(greeting)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+    use url::Url;
+
+    use super::*;
+    use crate::ScrapeError;
+    use crate::mock::MockProvider;
+
+    fn cache(provider: MockProvider) -> MemoryCache<MockProvider> {
+        MemoryCache::new(provider)
+    }
+
+    #[tokio::test]
+    async fn should_cache_images() {
+        let scraper =
+            cache(MockProvider::default().with_urls(Greeting::BuonGiorno, &["https://a.it/1.jpg"]));
+
+        for _ in 0..3 {
+            assert_eq!(
+                scraper.scrape(Greeting::BuonGiorno).await.unwrap(),
+                vec![Url::parse("https://a.it/1.jpg").unwrap()]
+            );
+        }
+        assert_eq!(scraper.provider().calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn should_expire_cached_images() {
+        let scraper =
+            cache(MockProvider::default().with_urls(Greeting::BuonGiorno, &["https://a.it/1.jpg"]))
+                .with_ttl(Duration::from_millis(50));
+
+        scraper.scrape(Greeting::BuonGiorno).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        scraper.scrape(Greeting::BuonGiorno).await.unwrap();
+        assert_eq!(scraper.provider().calls(), 2);
+    }
+
+    #[tokio::test]
+    async fn should_not_cache_errors() {
+        let scraper = cache(
+            MockProvider::default()
+                .with_error(Greeting::BuonGiorno, ScrapeError::no_images_found()),
+        );
+
+        assert!(scraper.scrape(Greeting::BuonGiorno).await.is_err());
+        assert!(scraper.scrape(Greeting::BuonGiorno).await.is_err());
+        assert_eq!(scraper.provider().calls(), 2);
+    }
+
+    #[tokio::test]
+    async fn should_evict_least_recently_used_greeting() {
+        let scraper = cache(
+            MockProvider::default()
+                .with_urls(Greeting::BuonGiorno, &["https://a.it/1.jpg"])
+                .with_urls(Greeting::BuonaNotte, &["https://a.it/2.jpg"])
+                .with_urls(Greeting::BuonaSerata, &["https://a.it/3.jpg"]),
+        )
+        .with_capacity(2);
+
+        scraper.scrape(Greeting::BuonGiorno).await.unwrap();
+        scraper.scrape(Greeting::BuonaNotte).await.unwrap();
+        scraper.scrape(Greeting::BuonGiorno).await.unwrap();
+        // evicts buona notte
+        scraper.scrape(Greeting::BuonaSerata).await.unwrap();
+        assert_eq!(scraper.provider().calls(), 3);
+
+        scraper.scrape(Greeting::BuonGiorno).await.unwrap();
+        assert_eq!(scraper.provider().calls(), 3);
+        scraper.scrape(Greeting::BuonaNotte).await.unwrap();
+        assert_eq!(scraper.provider().calls(), 4);
+    }
+
+    #[tokio::test]
+    async fn should_coalesce_concurrent_scrapes() {
+        let scraper = cache(
+            MockProvider::default()
+                .with_urls(Greeting::BuonGiorno, &["https://a.it/1.jpg"])
+                .with_delay(Duration::from_millis(50)),
+        );
+
+        let results =
+            futures_util::future::join_all((0..10).map(|_| scraper.scrape(Greeting::BuonGiorno)))
+                .await;
+        assert!(results.iter().all(|result| result.is_ok()));
+        assert_eq!(scraper.provider().calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn should_coalesce_concurrent_failing_scrapes() {
+        let scraper = cache(
+            MockProvider::default()
+                .with_error(Greeting::BuonGiorno, ScrapeError::no_images_found())
+                .with_delay(Duration::from_millis(50)),
+        );
+
+        let results =
+            futures_util::future::join_all((0..10).map(|_| scraper.scrape(Greeting::BuonGiorno)))
+                .await;
+        assert!(results.iter().all(|result| result.is_err()));
+        assert_eq!(scraper.provider().calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn should_invalidate_cache() {
+        let scraper = cache(
+            MockProvider::default()
+                .with_urls(Greeting::BuonGiorno, &["https://a.it/1.jpg"])
+                .with_urls(Greeting::BuonaNotte, &["https://a.it/2.jpg"]),
+        );
+
+        scraper.scrape(Greeting::BuonGiorno).await.unwrap();
+        scraper.scrape(Greeting::BuonaNotte).await.unwrap();
+        scraper.invalidate(Greeting::BuonGiorno);
+        scraper.scrape(Greeting::BuonGiorno).await.unwrap();
+        scraper.scrape(Greeting::BuonaNotte).await.unwrap();
+        assert_eq!(scraper.provider().calls(), 3);
+
+        scraper.clear();
+        scraper.scrape(Greeting::BuonaNotte).await.unwrap();
+        assert_eq!(scraper.provider().calls(), 4);
+    }
+
+    #[test]
+    fn should_tell_supported_greetings() {
+        let scraper = cache(MockProvider::default().with_urls(Greeting::BuonGiorno, &[]));
+        assert!(scraper.supports(Greeting::BuonGiorno));
+        assert!(!scraper.supports(Greeting::Natale));
+    }
+}
@@ -0,0 +1,394 @@
+//! # Download
+//!
+//! This module exposes the downloader, which fetches the scraped images validating their content
+
+use reqwest::Client;
+use reqwest::header::{ACCEPT, CONTENT_LENGTH, REFERER};
+use url::Url;
+
+use super::{ScrapeError, ScrapeResult, ScrapedImage};
+
+/// Default maximum size of a downloaded image: 10 MiB
+const DEFAULT_MAX_SIZE: u64 = 10 * 1024 * 1024;
+
+/// Something which can be downloaded by the [`Downloader`]: a [`ScrapedImage`] or a plain [`Url`]
+pub trait ImageSource {
+    /// Url of the image
+    fn image_url(&self) -> &Url;
+
+    /// Url of the page the image has been found in, sent as `Referer`
+    fn referer(&self) -> Option<&Url>;
+
+    /// Name of the provider which found the image, reported in the errors
+    fn provider(&self) -> &str;
+}
+
+impl ImageSource for ScrapedImage {
+    fn image_url(&self) -> &Url {
+        &self.url
+    }
+
+    fn referer(&self) -> Option<&Url> {
+        Some(&self.page_url)
+    }
+
+    fn provider(&self) -> &str {
+        &self.provider
+    }
+}
+
+impl ImageSource for Url {
+    fn image_url(&self) -> &Url {
+        self
+    }
+
+    fn referer(&self) -> Option<&Url> {
+        None
+    }
+
+    fn provider(&self) -> &str {
+        self.host_str().unwrap_or_default()
+    }
+}
+
+/// The format of an image, detected from its magic bytes
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum ImageFormat {
+    Avif,
+    Bmp,
+    Gif,
+    Jpeg,
+    Png,
+    Webp,
+}
+
+impl ImageFormat {
+    /// Detect the format of the image from its first bytes.
+    ///
+    /// Returns `None` if `bytes` don't start with the signature of a supported image format.
+    pub fn sniff(bytes: &[u8]) -> Option<Self> {
+        let brand = bytes.get(8..12);
+        if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+            Some(Self::Jpeg)
+        } else if bytes.starts_with(&[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]) {
+            Some(Self::Png)
+        } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+            Some(Self::Gif)
+        } else if bytes.starts_with(b"RIFF") && brand == Some(b"WEBP") {
+            Some(Self::Webp)
+        } else if bytes.get(4..8) == Some(b"ftyp") && matches!(brand, Some(b"avif" | b"avis")) {
+            Some(Self::Avif)
+        } else if is_bmp(bytes) {
+            Some(Self::Bmp)
+        } else {
+            None
+        }
+    }
+
+    /// Get the MIME type of the format
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            Self::Avif => "image/avif",
+            Self::Bmp => "image/bmp",
+            Self::Gif => "image/gif",
+            Self::Jpeg => "image/jpeg",
+            Self::Png => "image/png",
+            Self::Webp => "image/webp",
+        }
+    }
+
+    /// Get the usual file extension of the format, without the leading dot
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Avif => "avif",
+            Self::Bmp => "bmp",
+            Self::Gif => "gif",
+            Self::Jpeg => "jpg",
+            Self::Png => "png",
+            Self::Webp => "webp",
+        }
+    }
+}
+
+/// Whether `bytes` start with a bitmap file header (`BM`, reserved bytes set to zero) followed by a known DIB header size,
+/// so that texts starting with `BM` are not taken for images
+fn is_bmp(bytes: &[u8]) -> bool {
+    const DIB_HEADER_SIZES: &[u32] = &[12, 16, 40, 52, 56, 64, 108, 124];
+
+    let dib_header_size = bytes
+        .get(14..18)
+        .map(|size| u32::from_le_bytes([size[0], size[1], size[2], size[3]]));
+    bytes.starts_with(b"BM")
+        && bytes.get(6..10) == Some(&[0, 0, 0, 0])
+        && dib_header_size.is_some_and(|size| DIB_HEADER_SIZES.contains(&size))
+}
+
+/// An image downloaded by the [`Downloader`]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct DownloadedImage {
+    /// Url the image has been downloaded from
+    pub url: Url,
+    /// Content of the image
+    pub bytes: Vec<u8>,
+    /// Format of the image, detected from its content
+    pub format: ImageFormat,
+}
+
+impl DownloadedImage {
+    /// Get the MIME type of the image, detected from its content
+    pub fn mime_type(&self) -> &'static str {
+        self.format.mime_type()
+    }
+}
+
+/// Downloads the scraped images.
+///
+/// The format of the image is detected from its content, regardless of the `Content-Type` returned by the server,
+/// so that error pages served with `200 OK` are rejected with [`ScrapeError::NotAnImage`].
+/// Images larger than the maximum size (10 MiB by default) are rejected with [`ScrapeError::ImageTooLarge`].
+/// When downloading a [`ScrapedImage`], the page it has been found in is sent as `Referer`, to pass hotlink protections.
+///
+/// ```rust,no_run
+/// use buongiornissimo_rs::{BuongiornissimoCaffe, Downloader, Greeting, Scrape};
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     let images = BuongiornissimoCaffe::default().scrape_images(Greeting::BuonGiorno).await?;
+///     let image = Downloader::default()
+///         .with_max_size(5 * 1024 * 1024)
+///         .download(&images[0])
+///         .await?;
+///     println!("downloaded {} bytes of {}", image.bytes.len(), image.mime_type());
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Downloader {
+    client: Client,
+    max_size: u64,
+}
+
+impl Default for Downloader {
+    fn default() -> Self {
+        Self {
+            client: Client::default(),
+            max_size: DEFAULT_MAX_SIZE,
+        }
+    }
+}
+
+impl Downloader {
+    /// Set the [`Client`] used to download the images
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Set the maximum size of a downloaded image, in bytes
+    pub fn with_max_size(mut self, max_size: u64) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Get the maximum size of a downloaded image, in bytes
+    pub fn max_size(&self) -> u64 {
+        self.max_size
+    }
+
+    /// Download `image`, returning its content and its format
+    pub async fn download<I>(&self, image: &I) -> ScrapeResult<DownloadedImage>
+    where
+        I: ImageSource + ?Sized,
+    {
+        let url = image.image_url();
+        let provider = image.provider();
+        let http_error = |err| ScrapeError::from_reqwest(provider, url, err);
+        let too_large = || ScrapeError::ImageTooLarge {
+            url: Box::new(url.clone()),
+            max_size: self.max_size,
+        };
+
+        debug!("downloading {url}");
+        let mut request = self.client.get(url.clone()).header(ACCEPT, "image/*");
+        if let Some(referer) = image.referer() {
+            request = request.header(REFERER, referer.as_str());
+        }
+        let mut response = request.send().await.map_err(http_error)?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(ScrapeError::HttpStatus {
+                provider: provider.to_string(),
+                url: Box::new(url.clone()),
+                status: status.as_u16(),
+            });
+        }
+        // reject large images before downloading them, when the server declares their size
+        if response
+            .headers()
+            .get(CONTENT_LENGTH)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<u64>().ok())
+            .is_some_and(|length| length > self.max_size)
+        {
+            return Err(too_large());
+        }
+
+        let mut bytes = Vec::new();
+        while let Some(chunk) = response.chunk().await.map_err(http_error)? {
+            if (bytes.len() + chunk.len()) as u64 > self.max_size {
+                return Err(too_large());
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+
+        let Some(format) = ImageFormat::sniff(&bytes) else {
+            error!("{url} is not an image");
+            return Err(ScrapeError::NotAnImage {
+                url: Box::new(url.clone()),
+            });
+        };
+        debug!(
+            "downloaded {} bytes of {} from {url}",
+            bytes.len(),
+            format.mime_type()
+        );
+
+        Ok(DownloadedImage {
+            url: url.clone(),
+            bytes,
+            format,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::test_server::{Response, TestServer};
+
+    const PNG: &[u8] = &[
+        0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0x00, 0x00, 0x00, 0x0D,
+    ];
+
+    #[test]
+    fn should_sniff_image_format() {
+        assert_eq!(ImageFormat::sniff(PNG), Some(ImageFormat::Png));
+        assert_eq!(
+            ImageFormat::sniff(&[0xFF, 0xD8, 0xFF, 0xE0, 0x00]),
+            Some(ImageFormat::Jpeg)
+        );
+        assert_eq!(
+            ImageFormat::sniff(b"GIF89a\x01\x00"),
+            Some(ImageFormat::Gif)
+        );
+        assert_eq!(
+            ImageFormat::sniff(b"RIFF\x24\x00\x00\x00WEBPVP8 "),
+            Some(ImageFormat::Webp)
+        );
+        assert_eq!(
+            ImageFormat::sniff(b"\x00\x00\x00\x1cftypavif"),
+            Some(ImageFormat::Avif)
+        );
+        assert_eq!(
+            ImageFormat::sniff(
+                b"BM\x36\x00\x0c\x00\x00\x00\x00\x00\x36\x00\x00\x00\x28\x00\x00\x00"
+            ),
+            Some(ImageFormat::Bmp)
+        );
+        assert_eq!(ImageFormat::sniff(b"BM\x36\x00"), None);
+        assert_eq!(
+            ImageFormat::sniff(b"BMW dealers: page not found, please try again"),
+            None
+        );
+        assert_eq!(
+            ImageFormat::sniff(
+                b"BM\x36\x00\x0c\x00\x00\x00\x00\x00\x36\x00\x00\x00\x29\x00\x00\x00"
+            ),
+            None
+        );
+        assert_eq!(ImageFormat::sniff(b"<!DOCTYPE html><html>"), None);
+        assert_eq!(ImageFormat::sniff(b"RIFF\x24\x00\x00\x00WAVE"), None);
+        assert_eq!(ImageFormat::sniff(&[]), None);
+        assert_eq!(ImageFormat::Jpeg.mime_type(), "image/jpeg");
+        assert_eq!(ImageFormat::Webp.extension(), "webp");
+    }
+
+    #[tokio::test]
+    async fn should_download_image_with_referer() {
+        let server =
+            TestServer::start(|_| Response::ok(PNG).header("Content-Type", "text/html")).await;
+        let image = ScrapedImage::new(
+            server.url().join("/a.png").unwrap(),
+            server.url().join("/buongiorno/").unwrap(),
+            "Test",
+        );
+        let downloaded = Downloader::default().download(&image).await.unwrap();
+
+        assert_eq!(downloaded.bytes, PNG);
+        assert_eq!(downloaded.format, ImageFormat::Png);
+        assert_eq!(downloaded.mime_type(), "image/png");
+        assert_eq!(downloaded.url, image.url);
+        let request = &server.requests()[0];
+        assert_eq!(request.path, "/a.png");
+        assert_eq!(request.header("Referer"), Some(image.page_url.as_str()));
+    }
+
+    #[tokio::test]
+    async fn should_download_url() {
+        let server = TestServer::serve("GIF89a").await;
+        let downloaded = Downloader::default().download(&server.url()).await.unwrap();
+
+        assert_eq!(downloaded.format, ImageFormat::Gif);
+        assert_eq!(server.requests()[0].header("Referer"), None);
+    }
+
+    #[tokio::test]
+    async fn should_reject_non_images() {
+        let server = TestServer::start(|_| {
+            Response::ok("<html>hotlinking is not allowed</html>")
+                .header("Content-Type", "image/jpeg")
+        })
+        .await;
+        assert_eq!(
+            Downloader::default()
+                .download(&server.url())
+                .await
+                .unwrap_err(),
+            ScrapeError::NotAnImage {
+                url: Box::new(server.url())
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn should_reject_images_exceeding_max_size() {
+        let server = TestServer::serve(&format!("GIF89a{}", "0".repeat(1024))).await;
+        assert_eq!(
+            Downloader::default()
+                .with_max_size(1024)
+                .download(&server.url())
+                .await
+                .unwrap_err(),
+            ScrapeError::ImageTooLarge {
+                url: Box::new(server.url()),
+                max_size: 1024
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn should_return_status_error() {
+        let server = TestServer::start(|_| Response::status(403)).await;
+        let error = Downloader::default()
+            .download(&server.url())
+            .await
+            .unwrap_err();
+        assert_eq!(error.status(), Some(403));
+        assert_eq!(error.provider(), Some("127.0.0.1"));
+    }
+}
@@ -0,0 +1,513 @@
+//! # Error
+//!
+//! This module exposes the errors returned by the scrapers
+
+use std::error::Error as StdError;
+use std::fmt;
+use std::sync::Arc;
+
+use thiserror::Error;
+use url::Url;
+
+/// Scrape trait result
+pub type ScrapeResult<T> = Result<T, ScrapeError>;
+
+/// Scrape error
+#[derive(Debug, Clone, Error, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ScrapeError {
+    #[error("this scraper doesn't support this greeting type")]
+    UnsupportedGreeting,
+    /// The request failed
+    #[error("{provider}: request to {url} failed")]
+    Http {
+        /// Name of the provider which sent the request
+        provider: String,
+        /// The requested url
+        url: Box<Url>,
+        source: HttpError,
+    },
+    /// Could not connect to the provider
+    #[error("{provider}: could not connect to {url}")]
+    Connect {
+        /// Name of the provider which sent the request
+        provider: String,
+        /// The requested url
+        url: Box<Url>,
+        source: HttpError,
+    },
+    /// The provider replied with an unsuccessful status
+    #[error("{provider}: {url} replied with status {status}")]
+    HttpStatus {
+        /// Name of the provider which sent the request
+        provider: String,
+        /// The requested url
+        url: Box<Url>,
+        /// The http status code
+        status: u16,
+    },
+    #[error("css parser error: {0}")]
+    Css(String),
+    /// The page doesn't have the expected structure, probably because the provider changed its layout
+    #[error("{provider}: unexpected HTML at {url}: could not find `{selector}`")]
+    UnexpectedHtml {
+        /// Name of the provider which parsed the page
+        provider: String,
+        /// Url of the page
+        url: Box<Url>,
+        /// The selector which didn't match any element
+        selector: String,
+    },
+    /// The url is disallowed by the `robots.txt` of the provider
+    #[error("{provider}: {url} is disallowed by robots.txt")]
+    RobotsDisallowed {
+        /// Name of the provider which would have sent the request
+        provider: String,
+        /// The disallowed url
+        url: Box<Url>,
+    },
+    /// No image was found, probably because the provider changed its layout
+    #[error("{}", format_no_images(provider.as_deref(), url.as_deref()))]
+    NoImages {
+        /// Name of the provider which parsed the page, if known
+        provider: Option<String>,
+        /// Url of the page, if known
+        url: Option<Box<Url>>,
+    },
+    /// The downloaded content is not an image
+    #[error("{url} is not an image")]
+    NotAnImage {
+        /// The downloaded url
+        url: Box<Url>,
+    },
+    /// The downloaded image exceeds the maximum size
+    #[error("{url} exceeds the maximum size of {max_size} bytes")]
+    ImageTooLarge {
+        /// The downloaded url
+        url: Box<Url>,
+        /// The maximum size, in bytes
+        max_size: u64,
+    },
+    #[error("the provider didn't answer in time")]
+    Timeout,
+    /// The history of the delivered images could not be read or written
+    #[error("history store error: {0}")]
+    History(String),
+    #[error("all providers failed: {}", format_errors(.0))]
+    AllProvidersFailed(Vec<ScrapeError>),
+}
+
+impl ScrapeError {
+    /// Build a [`ScrapeError`] from the error returned by the http client, while requesting `url` for `provider`
+    pub(crate) fn from_reqwest(provider: &str, url: &Url, error: reqwest::Error) -> Self {
+        let provider = provider.to_string();
+        let url = Box::new(url.clone());
+        if let Some(status) = error.status() {
+            Self::HttpStatus {
+                provider,
+                url,
+                status: status.as_u16(),
+            }
+        } else if error.is_connect() {
+            Self::Connect {
+                provider,
+                url,
+                source: HttpError::from(error),
+            }
+        } else {
+            Self::Http {
+                provider,
+                url,
+                source: HttpError::from(error),
+            }
+        }
+    }
+
+    /// Build a [`ScrapeError::NoImages`] for the page at `url`, parsed by `provider`
+    pub(crate) fn no_images(provider: &str, url: &Url) -> Self {
+        Self::NoImages {
+            provider: Some(provider.to_string()),
+            url: Some(Box::new(url.clone())),
+        }
+    }
+
+    /// Build a [`ScrapeError::NoImages`] without knowing the provider and the page
+    pub(crate) fn no_images_found() -> Self {
+        Self::NoImages {
+            provider: None,
+            url: None,
+        }
+    }
+
+    /// Returns whether the error is transient, and so the request may succeed if retried.
+    ///
+    /// Connection errors, timeouts, `429 Too Many Requests` and `5xx` statuses are retryable.
+    /// [`ScrapeError::AllProvidersFailed`] is retryable if any of the providers failed with a retryable error.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Connect { .. } | Self::Timeout => true,
+            Self::Http { source, .. } => source.is_timeout(),
+            Self::HttpStatus { status, .. } => *status == 429 || (500..600).contains(status),
+            Self::AllProvidersFailed(errors) => errors.iter().any(Self::is_retryable),
+            Self::UnsupportedGreeting
+            | Self::Css(_)
+            | Self::UnexpectedHtml { .. }
+            | Self::RobotsDisallowed { .. }
+            | Self::NoImages { .. }
+            | Self::NotAnImage { .. }
+            | Self::ImageTooLarge { .. }
+            | Self::History(_) => false,
+        }
+    }
+
+    /// Returns the name of the provider which failed, if known
+    pub fn provider(&self) -> Option<&str> {
+        match self {
+            Self::Http { provider, .. }
+            | Self::Connect { provider, .. }
+            | Self::HttpStatus { provider, .. }
+            | Self::UnexpectedHtml { provider, .. }
+            | Self::RobotsDisallowed { provider, .. } => Some(provider),
+            Self::NoImages { provider, .. } => provider.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Returns the url of the page which couldn't be scraped, if known
+    pub fn url(&self) -> Option<&Url> {
+        match self {
+            Self::Http { url, .. }
+            | Self::Connect { url, .. }
+            | Self::HttpStatus { url, .. }
+            | Self::UnexpectedHtml { url, .. }
+            | Self::RobotsDisallowed { url, .. }
+            | Self::NotAnImage { url }
+            | Self::ImageTooLarge { url, .. } => Some(url),
+            Self::NoImages { url, .. } => url.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Returns the http status the provider replied with, if any
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            Self::HttpStatus { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+}
+
+fn format_no_images(provider: Option<&str>, url: Option<&Url>) -> String {
+    match (provider, url) {
+        (Some(provider), Some(url)) => format!("{provider}: could not find any image at {url}"),
+        (Some(provider), None) => format!("{provider}: could not find any image"),
+        (None, Some(url)) => format!("could not find any image at {url}"),
+        (None, None) => "could not find any image".to_string(),
+    }
+}
+
+fn format_errors(errors: &[ScrapeError]) -> String {
+    let errors: Vec<String> = errors.iter().map(ToString::to_string).collect();
+    format!("[{}]", errors.join("; "))
+}
+
+/// Error returned when parsing an unknown [`crate::Greeting`], with the near matches
+#[derive(Debug, Clone, Error, Eq, PartialEq)]
+#[error("unknown greeting `{input}`{}", format_suggestions(.suggestions))]
+pub struct ParseGreetingError {
+    input: String,
+    suggestions: Vec<&'static str>,
+}
+
+impl ParseGreetingError {
+    pub(crate) fn new(input: &str, suggestions: Vec<&'static str>) -> Self {
+        Self {
+            input: input.to_string(),
+            suggestions,
+        }
+    }
+
+    /// Get the string which couldn't be parsed
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+
+    /// Get the slugs of the greetings near to the input, nearest first
+    pub fn suggestions(&self) -> &[&'static str] {
+        &self.suggestions
+    }
+}
+
+fn format_suggestions(suggestions: &[&str]) -> String {
+    if suggestions.is_empty() {
+        return String::new();
+    }
+    let suggestions: Vec<String> = suggestions
+        .iter()
+        .map(|suggestion| format!("`{suggestion}`"))
+        .collect();
+    format!("; did you mean {}?", suggestions.join(", "))
+}
+
+/// An error returned by the http client.
+///
+/// The original error is shared, so that [`ScrapeError`] can be cloned, and it's returned by [`StdError::source`].
+#[derive(Debug, Clone)]
+pub struct HttpError(Arc<HttpErrorInner>);
+
+#[derive(Debug)]
+struct HttpErrorInner {
+    message: String,
+    timeout: bool,
+    error: Option<reqwest::Error>,
+}
+
+impl HttpError {
+    /// Returns whether the request timed out
+    pub fn is_timeout(&self) -> bool {
+        self.0.timeout
+    }
+
+    /// Returns the original error of the http client, if available
+    pub fn reqwest_error(&self) -> Option<&reqwest::Error> {
+        self.0.error.as_ref()
+    }
+}
+
+impl From<reqwest::Error> for HttpError {
+    fn from(error: reqwest::Error) -> Self {
+        Self(Arc::new(HttpErrorInner {
+            message: error.to_string(),
+            timeout: error.is_timeout(),
+            error: Some(error),
+        }))
+    }
+}
+
+impl fmt::Display for HttpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0.message)
+    }
+}
+
+impl StdError for HttpError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        self.0
+            .error
+            .as_ref()
+            .map(|error| error as &(dyn StdError + 'static))
+    }
+}
+
+impl PartialEq for HttpError {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.message == other.0.message && self.0.timeout == other.0.timeout
+    }
+}
+
+impl Eq for HttpError {}
+
+/// [`HttpError`] is serialized with its message, while the original error is lost
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SerializedHttpError {
+    message: String,
+    timeout: bool,
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for HttpError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        SerializedHttpError {
+            message: self.0.message.clone(),
+            timeout: self.0.timeout,
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for HttpError {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let error = SerializedHttpError::deserialize(deserializer)?;
+        Ok(Self(Arc::new(HttpErrorInner {
+            message: error.message,
+            timeout: error.timeout,
+            error: None,
+        })))
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn url() -> Url {
+        Url::parse("https://augurando.it/immagini-buongiorno/").unwrap()
+    }
+
+    fn status_error(status: u16) -> ScrapeError {
+        ScrapeError::HttpStatus {
+            provider: "Augurando".to_string(),
+            url: Box::new(url()),
+            status,
+        }
+    }
+
+    async fn connect_error() -> ScrapeError {
+        let url = Url::parse("http://127.0.0.1:1/").unwrap();
+        let error = reqwest::get(url.clone()).await.unwrap_err();
+        ScrapeError::from_reqwest("Augurando", &url, error)
+    }
+
+    #[test]
+    fn should_tell_whether_error_is_retryable() {
+        assert!(ScrapeError::Timeout.is_retryable());
+        assert!(status_error(429).is_retryable());
+        assert!(status_error(502).is_retryable());
+        assert!(!status_error(404).is_retryable());
+        assert!(!ScrapeError::no_images_found().is_retryable());
+        assert!(!ScrapeError::UnsupportedGreeting.is_retryable());
+        assert!(
+            ScrapeError::AllProvidersFailed(vec![
+                ScrapeError::no_images_found(),
+                status_error(503)
+            ])
+            .is_retryable()
+        );
+        assert!(
+            !ScrapeError::AllProvidersFailed(vec![
+                ScrapeError::no_images_found(),
+                ScrapeError::UnsupportedGreeting
+            ])
+            .is_retryable()
+        );
+    }
+
+    #[tokio::test]
+    async fn should_build_connect_error_from_reqwest() {
+        let error = connect_error().await;
+        assert!(matches!(error, ScrapeError::Connect { .. }));
+        assert!(error.is_retryable());
+        assert_eq!(error.provider(), Some("Augurando"));
+        assert_eq!(error.url().unwrap().as_str(), "http://127.0.0.1:1/");
+        assert_eq!(error.status(), None);
+    }
+
+    #[tokio::test]
+    async fn should_chain_error_sources() {
+        let error = connect_error().await;
+        let source = error.source().unwrap();
+        assert!(source.downcast_ref::<HttpError>().is_some());
+        let reqwest_error = source.source().unwrap();
+        assert!(reqwest_error.downcast_ref::<reqwest::Error>().is_some());
+        assert_eq!(
+            error.to_string(),
+            "Augurando: could not connect to http://127.0.0.1:1/"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn should_serialize_errors() {
+        let errors = vec![
+            status_error(503),
+            ScrapeError::no_images_found(),
+            ScrapeError::AllProvidersFailed(vec![
+                ScrapeError::Css("invalid selector".to_string()),
+                ScrapeError::Http {
+                    provider: "Augurando".to_string(),
+                    url: Box::new(url()),
+                    source: HttpError(Arc::new(HttpErrorInner {
+                        message: "operation timed out".to_string(),
+                        timeout: true,
+                        error: None,
+                    })),
+                },
+            ]),
+        ];
+        let json = serde_json::to_string(&errors).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Vec<ScrapeError>>(&json).unwrap(),
+            errors
+        );
+        assert_eq!(
+            serde_json::to_value(status_error(503)).unwrap(),
+            serde_json::json!({
+                "HttpStatus": {
+                    "provider": "Augurando",
+                    "url": "https://augurando.it/immagini-buongiorno/",
+                    "status": 503
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn should_display_structured_errors() {
+        assert_eq!(
+            status_error(502).to_string(),
+            "Augurando: https://augurando.it/immagini-buongiorno/ replied with status 502"
+        );
+        assert_eq!(
+            ScrapeError::UnexpectedHtml {
+                provider: "Augurando".to_string(),
+                url: Box::new(url()),
+                selector: "main".to_string(),
+            }
+            .to_string(),
+            "Augurando: unexpected HTML at https://augurando.it/immagini-buongiorno/: could not find `main`"
+        );
+        assert_eq!(status_error(404).provider(), Some("Augurando"));
+        assert_eq!(status_error(404).status(), Some(404));
+        assert_eq!(ScrapeError::no_images_found().provider(), None);
+    }
+
+    #[test]
+    fn should_tell_where_no_images_were_found() {
+        let error = ScrapeError::no_images("Augurando", &url());
+        assert_eq!(
+            error.to_string(),
+            "Augurando: could not find any image at https://augurando.it/immagini-buongiorno/"
+        );
+        assert_eq!(error.provider(), Some("Augurando"));
+        assert_eq!(error.url(), Some(&url()));
+        assert_eq!(
+            ScrapeError::no_images_found().to_string(),
+            "could not find any image"
+        );
+        assert_eq!(ScrapeError::no_images_found().url(), None);
+    }
+
+    #[test]
+    fn should_not_repeat_source_message() {
+        let error = ScrapeError::Http {
+            provider: "Augurando".to_string(),
+            url: Box::new(url()),
+            source: HttpError(Arc::new(HttpErrorInner {
+                message: "operation timed out".to_string(),
+                timeout: true,
+                error: None,
+            })),
+        };
+        assert_eq!(
+            error.to_string(),
+            "Augurando: request to https://augurando.it/immagini-buongiorno/ failed"
+        );
+        assert_eq!(error.source().unwrap().to_string(), "operation timed out");
+    }
+
+    #[test]
+    fn should_keep_error_small() {
+        assert!(std::mem::size_of::<ScrapeError>() <= 64);
+    }
+}
@@ -0,0 +1,87 @@
+//! # Fs
+//!
+//! Utilities to persist files on disk
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Counter of the temporary files created by this process
+static TMP_FILES: AtomicUsize = AtomicUsize::new(0);
+
+/// Write `content` at `path`, creating its parent directories.
+///
+/// The content is written to a temporary file in the same directory first, and then renamed to `path`,
+/// so that readers never see a partial file. Each write uses its own temporary file,
+/// so that concurrent writers (even in different processes) never interleave.
+pub(crate) async fn write_atomic(path: &Path, content: impl AsRef<[u8]>) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    let tmp_path = tmp_path(path);
+    let result = async {
+        tokio::fs::write(&tmp_path, content).await?;
+        tokio::fs::rename(&tmp_path, path).await
+    }
+    .await;
+    if result.is_err() {
+        let _ = tokio::fs::remove_file(&tmp_path).await;
+    }
+    result
+}
+
+/// Get a temporary path next to `path`, unique for this write
+fn tmp_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(format!(
+        ".{}.{}.tmp",
+        std::process::id(),
+        TMP_FILES.fetch_add(1, Ordering::Relaxed)
+    ));
+    path.with_file_name(file_name)
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn should_get_unique_tmp_paths() {
+        let path = Path::new("/tmp/cache/buongiorno.cache");
+        let first = tmp_path(path);
+        let second = tmp_path(path);
+        assert_ne!(first, second);
+        assert_eq!(first.parent(), path.parent());
+        assert!(
+            first
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .starts_with("buongiorno.cache.")
+        );
+    }
+
+    #[tokio::test]
+    async fn should_write_atomically_from_concurrent_writers() {
+        let directory = TempDir::new().unwrap();
+        let path = directory.path().join("nested/file.txt");
+        let contents: Vec<String> = (0..16).map(|i| format!("{i}\n").repeat(1000)).collect();
+        let results = futures_util::future::join_all(
+            contents.iter().map(|content| write_atomic(&path, content)),
+        )
+        .await;
+        assert!(results.iter().all(Result::is_ok));
+
+        // the file is one of the written contents, and no temporary file is left
+        let content = tokio::fs::read_to_string(&path).await.unwrap();
+        assert!(contents.contains(&content));
+        assert_eq!(
+            std::fs::read_dir(path.parent().unwrap()).unwrap().count(),
+            1
+        );
+    }
+}
@@ -0,0 +1,496 @@
+//! # Greeting
+//!
+//! This module implements the conversion of the greetings from and to their slugs and their Italian labels,
+//! and the classification of the greetings in categories
+
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::Weekday;
+
+use super::{Greeting, ParseGreetingError};
+
+/// Maximum amount of near matches suggested by [`ParseGreetingError`]
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Describes the category of a [`Greeting`]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum GreetingCategory {
+    /// The ordinary greeting, suitable for any day
+    Daily,
+    /// Greetings for a moment of the day, such as lunch or night
+    TimeOfDay,
+    /// Greetings for a day of the week, including the weekend
+    Weekday,
+    /// Feasts which fall on the same date every year
+    FixedFeast,
+    /// Feasts whose date changes every year, such as Easter
+    MoveableFeast,
+    /// Greetings for personal occasions, such as birthdays
+    Personal,
+}
+
+impl Greeting {
+    /// All the greetings, including [`Greeting::BuonGiornoWeekday`] for each weekday
+    pub const ALL: &'static [Greeting] = &[
+        Greeting::BuonGiorno,
+        Greeting::BuonGiornoWeekday(Weekday::Mon),
+        Greeting::BuonGiornoWeekday(Weekday::Tue),
+        Greeting::BuonGiornoWeekday(Weekday::Wed),
+        Greeting::BuonGiornoWeekday(Weekday::Thu),
+        Greeting::BuonGiornoWeekday(Weekday::Fri),
+        Greeting::BuonGiornoWeekday(Weekday::Sat),
+        Greeting::BuonGiornoWeekday(Weekday::Sun),
+        Greeting::Weekend,
+        Greeting::BuonPomeriggio,
+        Greeting::BuonPranzo,
+        Greeting::BuonaNotte,
+        Greeting::BuonaSerata,
+        Greeting::BuonaCena,
+        Greeting::Compleanno,
+        Greeting::Capodanno,
+        Greeting::Epifania,
+        Greeting::SanValentino,
+        Greeting::GiovediGrasso,
+        Greeting::MartediGrasso,
+        Greeting::MercolediCeneri,
+        Greeting::FestaDelleDonne,
+        Greeting::FestaDelPapa,
+        Greeting::FestaDellaMamma,
+        Greeting::DomenicaDellePalme,
+        Greeting::Pasqua,
+        Greeting::Pasquetta,
+        Greeting::Liberazione,
+        Greeting::FestaDeiLavoratori,
+        Greeting::Ascensione,
+        Greeting::Pentecoste,
+        Greeting::DueGiugno,
+        Greeting::SantissimaTrinita,
+        Greeting::FestaDellaRepubblica,
+        Greeting::SacroCuoreDiGesu,
+        Greeting::CuoreImmacolatoDiMaria,
+        Greeting::CorpusDomini,
+        Greeting::Ferragosto,
+        Greeting::Halloween,
+        Greeting::Ognissanti,
+        Greeting::Defunti,
+        Greeting::SanNicola,
+        Greeting::SantAmbrogio,
+        Greeting::ImmacolataConcenzione,
+        Greeting::SantaLucia,
+        Greeting::VigiliaDiNatale,
+        Greeting::Natale,
+        Greeting::SantoStefano,
+        Greeting::SanSilvestro,
+    ];
+
+    /// Returns an iterator over all the greetings, including [`Greeting::BuonGiornoWeekday`] for each weekday
+    pub fn all() -> impl Iterator<Item = Greeting> {
+        Self::ALL.iter().copied()
+    }
+
+    /// Get the category of the greeting
+    pub fn category(&self) -> GreetingCategory {
+        match self {
+            Self::BuonGiorno => GreetingCategory::Daily,
+            Self::BuonPomeriggio
+            | Self::BuonPranzo
+            | Self::BuonaNotte
+            | Self::BuonaSerata
+            | Self::BuonaCena => GreetingCategory::TimeOfDay,
+            Self::BuonGiornoWeekday(_) | Self::Weekend => GreetingCategory::Weekday,
+            Self::Capodanno
+            | Self::Epifania
+            | Self::SanValentino
+            | Self::FestaDelleDonne
+            | Self::FestaDelPapa
+            | Self::Liberazione
+            | Self::FestaDeiLavoratori
+            | Self::DueGiugno
+            | Self::FestaDellaRepubblica
+            | Self::Ferragosto
+            | Self::Halloween
+            | Self::Ognissanti
+            | Self::Defunti
+            | Self::SanNicola
+            | Self::SantAmbrogio
+            | Self::ImmacolataConcenzione
+            | Self::SantaLucia
+            | Self::VigiliaDiNatale
+            | Self::Natale
+            | Self::SantoStefano
+            | Self::SanSilvestro => GreetingCategory::FixedFeast,
+            Self::GiovediGrasso
+            | Self::MartediGrasso
+            | Self::MercolediCeneri
+            | Self::FestaDellaMamma
+            | Self::DomenicaDellePalme
+            | Self::Pasqua
+            | Self::Pasquetta
+            | Self::Ascensione
+            | Self::Pentecoste
+            | Self::SantissimaTrinita
+            | Self::SacroCuoreDiGesu
+            | Self::CuoreImmacolatoDiMaria
+            | Self::CorpusDomini => GreetingCategory::MoveableFeast,
+            Self::Compleanno => GreetingCategory::Personal,
+        }
+    }
+
+    /// Get the stable kebab-case slug of the greeting, such as `pasqua` or `buon-lunedi`.
+    ///
+    /// The slug is returned by [`fmt::Display`] and parsed by [`FromStr`].
+    pub fn slug(&self) -> &'static str {
+        match self {
+            Self::BuonGiorno => "buongiorno",
+            Self::BuonGiornoWeekday(Weekday::Mon) => "buon-lunedi",
+            Self::BuonGiornoWeekday(Weekday::Tue) => "buon-martedi",
+            Self::BuonGiornoWeekday(Weekday::Wed) => "buon-mercoledi",
+            Self::BuonGiornoWeekday(Weekday::Thu) => "buon-giovedi",
+            Self::BuonGiornoWeekday(Weekday::Fri) => "buon-venerdi",
+            Self::BuonGiornoWeekday(Weekday::Sat) => "buon-sabato",
+            Self::BuonGiornoWeekday(Weekday::Sun) => "buona-domenica",
+            Self::Weekend => "buon-weekend",
+            Self::BuonPomeriggio => "buon-pomeriggio",
+            Self::BuonPranzo => "buon-pranzo",
+            Self::BuonaNotte => "buonanotte",
+            Self::BuonaSerata => "buona-serata",
+            Self::BuonaCena => "buona-cena",
+            Self::Compleanno => "compleanno",
+            Self::Capodanno => "capodanno",
+            Self::Epifania => "epifania",
+            Self::SanValentino => "san-valentino",
+            Self::GiovediGrasso => "giovedi-grasso",
+            Self::MartediGrasso => "martedi-grasso",
+            Self::MercolediCeneri => "mercoledi-delle-ceneri",
+            Self::FestaDelleDonne => "festa-delle-donne",
+            Self::FestaDelPapa => "festa-del-papa",
+            Self::FestaDellaMamma => "festa-della-mamma",
+            Self::DomenicaDellePalme => "domenica-delle-palme",
+            Self::Pasqua => "pasqua",
+            Self::Pasquetta => "pasquetta",
+            Self::Liberazione => "liberazione",
+            Self::FestaDeiLavoratori => "festa-dei-lavoratori",
+            Self::Ascensione => "ascensione",
+            Self::Pentecoste => "pentecoste",
+            Self::DueGiugno => "due-giugno",
+            Self::SantissimaTrinita => "santissima-trinita",
+            Self::FestaDellaRepubblica => "festa-della-repubblica",
+            Self::SacroCuoreDiGesu => "sacro-cuore-di-gesu",
+            Self::CuoreImmacolatoDiMaria => "cuore-immacolato-di-maria",
+            Self::CorpusDomini => "corpus-domini",
+            Self::Ferragosto => "ferragosto",
+            Self::Halloween => "halloween",
+            Self::Ognissanti => "ognissanti",
+            Self::Defunti => "defunti",
+            Self::SanNicola => "san-nicola",
+            Self::SantAmbrogio => "sant-ambrogio",
+            Self::ImmacolataConcenzione => "immacolata-concezione",
+            Self::SantaLucia => "santa-lucia",
+            Self::VigiliaDiNatale => "vigilia-di-natale",
+            Self::Natale => "natale",
+            Self::SantoStefano => "santo-stefano",
+            Self::SanSilvestro => "san-silvestro",
+        }
+    }
+
+    /// Get the Italian label of the greeting, such as `Buona Pasqua` or `Buon lunedì`
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::BuonGiorno => "Buongiorno",
+            Self::BuonGiornoWeekday(Weekday::Mon) => "Buon lunedì",
+            Self::BuonGiornoWeekday(Weekday::Tue) => "Buon martedì",
+            Self::BuonGiornoWeekday(Weekday::Wed) => "Buon mercoledì",
+            Self::BuonGiornoWeekday(Weekday::Thu) => "Buon giovedì",
+            Self::BuonGiornoWeekday(Weekday::Fri) => "Buon venerdì",
+            Self::BuonGiornoWeekday(Weekday::Sat) => "Buon sabato",
+            Self::BuonGiornoWeekday(Weekday::Sun) => "Buona domenica",
+            Self::Weekend => "Buon weekend",
+            Self::BuonPomeriggio => "Buon pomeriggio",
+            Self::BuonPranzo => "Buon pranzo",
+            Self::BuonaNotte => "Buonanotte",
+            Self::BuonaSerata => "Buona serata",
+            Self::BuonaCena => "Buona cena",
+            Self::Compleanno => "Buon compleanno",
+            Self::Capodanno => "Buon anno",
+            Self::Epifania => "Buona Epifania",
+            Self::SanValentino => "Buon San Valentino",
+            Self::GiovediGrasso => "Buon Giovedì grasso",
+            Self::MartediGrasso => "Buon Martedì grasso",
+            Self::MercolediCeneri => "Buon Mercoledì delle Ceneri",
+            Self::FestaDelleDonne => "Buona Festa della donna",
+            Self::FestaDelPapa => "Buona Festa del papà",
+            Self::FestaDellaMamma => "Buona Festa della mamma",
+            Self::DomenicaDellePalme => "Buona Domenica delle Palme",
+            Self::Pasqua => "Buona Pasqua",
+            Self::Pasquetta => "Buona Pasquetta",
+            Self::Liberazione => "Buona Festa della Liberazione",
+            Self::FestaDeiLavoratori => "Buona Festa dei lavoratori",
+            Self::Ascensione => "Buona Ascensione",
+            Self::Pentecoste => "Buona Pentecoste",
+            Self::DueGiugno => "Buon 2 giugno",
+            Self::SantissimaTrinita => "Buona Santissima Trinità",
+            Self::FestaDellaRepubblica => "Buona Festa della Repubblica",
+            Self::SacroCuoreDiGesu => "Buon Sacro Cuore di Gesù",
+            Self::CuoreImmacolatoDiMaria => "Buon Cuore Immacolato di Maria",
+            Self::CorpusDomini => "Buon Corpus Domini",
+            Self::Ferragosto => "Buon Ferragosto",
+            Self::Halloween => "Buon Halloween",
+            Self::Ognissanti => "Buon Ognissanti",
+            Self::Defunti => "Commemorazione dei defunti",
+            Self::SanNicola => "Buon San Nicola",
+            Self::SantAmbrogio => "Buon Sant'Ambrogio",
+            Self::ImmacolataConcenzione => "Buona Immacolata Concezione",
+            Self::SantaLucia => "Buona Santa Lucia",
+            Self::VigiliaDiNatale => "Buona Vigilia di Natale",
+            Self::Natale => "Buon Natale",
+            Self::SantoStefano => "Buon Santo Stefano",
+            Self::SanSilvestro => "Buon San Silvestro",
+        }
+    }
+}
+
+impl fmt::Display for Greeting {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.slug())
+    }
+}
+
+impl FromStr for Greeting {
+    type Err = ParseGreetingError;
+
+    /// Parse a greeting from its slug.
+    ///
+    /// Parsing is case insensitive and tolerant: accents are ignored, while spaces, underscores and apostrophes are considered as dashes,
+    /// so that `Buon lunedì` is parsed as `buon-lunedi`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let normalized = normalize(s);
+        if let Some(greeting) = Greeting::ALL
+            .iter()
+            .find(|greeting| greeting.slug() == normalized)
+        {
+            return Ok(*greeting);
+        }
+
+        Err(ParseGreetingError::new(s, suggestions(&normalized)))
+    }
+}
+
+/// Normalize a greeting to be compared with the slugs
+fn normalize(s: &str) -> String {
+    let slug: String = s
+        .trim()
+        .to_lowercase()
+        .chars()
+        .map(|c| match c {
+            'à' | 'á' => 'a',
+            'è' | 'é' => 'e',
+            'ì' | 'í' => 'i',
+            'ò' | 'ó' => 'o',
+            'ù' | 'ú' => 'u',
+            ' ' | '_' | '\'' | '’' => '-',
+            c => c,
+        })
+        .collect();
+    slug.split('-')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Get the slugs which are near to `normalized`, nearest first
+fn suggestions(normalized: &str) -> Vec<&'static str> {
+    if normalized.is_empty() {
+        return Vec::new();
+    }
+    let max_distance = (normalized.chars().count() / 3).max(2);
+    let mut near: Vec<(usize, &'static str)> = Greeting::ALL
+        .iter()
+        .map(Greeting::slug)
+        .filter_map(|slug| {
+            let distance = if slug.starts_with(normalized) {
+                0
+            } else {
+                distance(normalized, slug)
+            };
+            (distance <= max_distance).then_some((distance, slug))
+        })
+        .collect();
+    near.sort();
+    near.into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, slug)| slug)
+        .collect()
+}
+
+/// Levenshtein distance between `a` and `b`
+fn distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a != *b);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Greeting {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.slug())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Greeting {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let slug = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        slug.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use std::collections::HashSet;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_iterate_all_greetings() {
+        let greetings: HashSet<Greeting> = Greeting::all().collect();
+        assert_eq!(greetings.len(), Greeting::ALL.len());
+        assert_eq!(greetings.len(), 49);
+        assert_eq!(
+            Greeting::all()
+                .filter(|greeting| greeting.category() == GreetingCategory::Weekday)
+                .count(),
+            8
+        );
+    }
+
+    #[test]
+    fn should_classify_greetings() {
+        assert_eq!(Greeting::BuonGiorno.category(), GreetingCategory::Daily);
+        assert_eq!(Greeting::BuonaNotte.category(), GreetingCategory::TimeOfDay);
+        assert_eq!(
+            Greeting::BuonGiornoWeekday(Weekday::Fri).category(),
+            GreetingCategory::Weekday
+        );
+        assert_eq!(Greeting::Natale.category(), GreetingCategory::FixedFeast);
+        assert_eq!(Greeting::Pasqua.category(), GreetingCategory::MoveableFeast);
+        assert_eq!(Greeting::Compleanno.category(), GreetingCategory::Personal);
+        // every category has at least a greeting
+        let categories: HashSet<GreetingCategory> = Greeting::all()
+            .map(|greeting| greeting.category())
+            .collect();
+        assert_eq!(categories.len(), 6);
+    }
+
+    #[test]
+    fn should_round_trip_every_greeting() {
+        for greeting in Greeting::ALL {
+            assert_eq!(greeting.to_string().parse::<Greeting>(), Ok(*greeting));
+        }
+    }
+
+    #[test]
+    fn should_have_unique_slugs_and_labels() {
+        let slugs: HashSet<&str> = Greeting::ALL.iter().map(Greeting::slug).collect();
+        assert_eq!(slugs.len(), Greeting::ALL.len());
+        let labels: HashSet<&str> = Greeting::ALL.iter().map(Greeting::label).collect();
+        assert_eq!(labels.len(), Greeting::ALL.len());
+        assert!(
+            slugs
+                .iter()
+                .all(|slug| slug.chars().all(|c| c.is_ascii_lowercase() || c == '-'))
+        );
+    }
+
+    #[test]
+    fn should_display_slug_and_label() {
+        assert_eq!(Greeting::Pasqua.to_string(), "pasqua");
+        assert_eq!(Greeting::Pasqua.label(), "Buona Pasqua");
+        assert_eq!(
+            Greeting::BuonGiornoWeekday(Weekday::Mon).to_string(),
+            "buon-lunedi"
+        );
+        assert_eq!(
+            Greeting::BuonGiornoWeekday(Weekday::Mon).label(),
+            "Buon lunedì"
+        );
+        assert_eq!(
+            Greeting::ImmacolataConcenzione.to_string(),
+            "immacolata-concezione"
+        );
+    }
+
+    #[test]
+    fn should_parse_greeting_leniently() {
+        assert_eq!("Pasqua".parse(), Ok(Greeting::Pasqua));
+        assert_eq!(
+            " Buon lunedì ".parse(),
+            Ok(Greeting::BuonGiornoWeekday(Weekday::Mon))
+        );
+        assert_eq!("sant'ambrogio".parse(), Ok(Greeting::SantAmbrogio));
+        assert_eq!("festa_del_papà".parse(), Ok(Greeting::FestaDelPapa));
+    }
+
+    #[test]
+    fn should_suggest_near_matches() {
+        let error = "pasqa".parse::<Greeting>().unwrap_err();
+        assert_eq!(error.input(), "pasqa");
+        assert_eq!(error.suggestions(), &["pasqua"]);
+        assert_eq!(
+            error.to_string(),
+            "unknown greeting `pasqa`; did you mean `pasqua`?"
+        );
+
+        let error = "buon-".parse::<Greeting>().unwrap_err();
+        assert_eq!(error.suggestions().len(), MAX_SUGGESTIONS);
+
+        let error = "caffè".parse::<Greeting>().unwrap_err();
+        assert!(error.suggestions().is_empty());
+        assert_eq!(error.to_string(), "unknown greeting `caffè`");
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn should_serialize_greeting_as_slug() {
+        assert_eq!(
+            serde_json::to_string(&Greeting::BuonGiornoWeekday(Weekday::Mon)).unwrap(),
+            r#""buon-lunedi""#
+        );
+        assert_eq!(
+            serde_json::from_str::<Vec<Greeting>>(r#"["pasqua", "Buon lunedì"]"#).unwrap(),
+            vec![Greeting::Pasqua, Greeting::BuonGiornoWeekday(Weekday::Mon)]
+        );
+        assert!(
+            serde_json::from_str::<Greeting>(r#""pasqa""#)
+                .unwrap_err()
+                .to_string()
+                .contains("did you mean `pasqua`?")
+        );
+    }
+
+    #[test]
+    fn should_compute_distance() {
+        assert_eq!(distance("pasqua", "pasqua"), 0);
+        assert_eq!(distance("pasqa", "pasqua"), 1);
+        assert_eq!(distance("natale", "natela"), 2);
+        assert_eq!(distance("", "abc"), 3);
+    }
+}
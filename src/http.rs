@@ -0,0 +1,477 @@
+//! # Http
+//!
+//! This module exposes the http client shared by the providers to fetch the pages
+
+mod conditional;
+mod rate_limit;
+mod retry;
+mod robots;
+
+use std::time::Duration;
+
+use reqwest::Client;
+use reqwest::StatusCode;
+use reqwest::header::RETRY_AFTER;
+use url::Url;
+
+pub(crate) use self::conditional::Page;
+use self::conditional::{PageCache, Validators};
+pub use self::rate_limit::RateLimit;
+pub use self::retry::RetryPolicy;
+pub use self::robots::RobotsTxt;
+use super::{ScrapeError, ScrapeResult};
+
+/// The client used by the providers to fetch the pages, which applies the [`RetryPolicy`], the [`RateLimit`] and the [`RobotsTxt`] rules.
+///
+/// Parsed pages are cached with their `ETag` and `Last-Modified` validators, so that they're requested conditionally
+/// and they're not downloaded and parsed again unless modified.
+#[derive(Debug, Clone)]
+pub(crate) struct HttpClient {
+    provider: &'static str,
+    client: Client,
+    retry: RetryPolicy,
+    rate_limit: Option<RateLimit>,
+    robots: Option<RobotsTxt>,
+    pages: PageCache,
+}
+
+/// The response to a (possibly conditional) request
+enum Fetched {
+    /// The body of the page, with its validators
+    Modified {
+        body: String,
+        validators: Validators,
+    },
+    /// The page has not been modified since the validators were returned
+    NotModified,
+}
+
+/// A failed attempt to fetch a page
+struct Failure {
+    error: ScrapeError,
+    retry_after: Option<Duration>,
+}
+
+impl HttpClient {
+    /// Create a new [`HttpClient`] for `provider`, with a default [`Client`], without retries, rate limit and `robots.txt` compliance
+    pub fn new(provider: &'static str) -> Self {
+        Self {
+            provider,
+            client: Client::default(),
+            retry: RetryPolicy::none(),
+            rate_limit: None,
+            robots: None,
+            pages: PageCache::default(),
+        }
+    }
+
+    /// Set the [`Client`] used to send the requests
+    pub fn set_client(&mut self, client: Client) {
+        self.client = client;
+    }
+
+    /// Set the [`RetryPolicy`] applied to the requests
+    pub fn set_retry(&mut self, retry: RetryPolicy) {
+        self.retry = retry;
+    }
+
+    /// Set the [`RateLimit`] applied to the requests
+    pub fn set_rate_limit(&mut self, rate_limit: RateLimit) {
+        self.rate_limit = Some(rate_limit);
+    }
+
+    /// Comply with the `robots.txt` of the hosts, as described by `robots`
+    pub fn set_robots_txt(&mut self, robots: RobotsTxt) {
+        self.robots = Some(robots);
+    }
+
+    /// Get the page at `url`, parsed with `parse`.
+    ///
+    /// If the page has been parsed before, it's requested with the `If-None-Match` and `If-Modified-Since` headers;
+    /// when the server replies with `304 Not Modified`, the previously parsed page is returned without parsing the body again.
+    pub async fn get_page<F>(&self, url: &Url, parse: F) -> ScrapeResult<Page>
+    where
+        F: FnOnce(&str) -> ScrapeResult<Page>,
+    {
+        let cached = self.pages.get(url);
+        match self
+            .fetch(url, cached.as_ref().map(|(validators, _)| validators))
+            .await?
+        {
+            Fetched::Modified { body, validators } => {
+                debug!("got body of length {} at {}", body.len(), url);
+                trace!("body: {}", body);
+                let page = parse(&body)?;
+                self.pages.insert(url, validators, page.clone());
+                Ok(page)
+            }
+            Fetched::NotModified => match cached {
+                Some((_, page)) => {
+                    debug!("{url} has not been modified; reusing the parsed page");
+                    Ok(page)
+                }
+                None => Err(self.status_error(url, StatusCode::NOT_MODIFIED)),
+            },
+        }
+    }
+
+    /// Request `url`, conditionally if `validators` are provided, retrying on transient errors as described by the [`RetryPolicy`]
+    async fn fetch(&self, url: &Url, validators: Option<&Validators>) -> ScrapeResult<Fetched> {
+        let crawl_delay = match &self.robots {
+            Some(robots) => robots.check(&self.client, self.provider, url).await?,
+            None => None,
+        };
+        let rate_limit = match (self.rate_limit, crawl_delay) {
+            (Some(rate_limit), Some(delay)) => {
+                Some(RateLimit::new(rate_limit.min_interval().max(delay)))
+            }
+            (None, Some(delay)) => Some(RateLimit::new(delay)),
+            (rate_limit, None) => rate_limit,
+        };
+
+        let mut attempt = 0;
+        loop {
+            let failure = match self.try_fetch(url, validators, rate_limit).await {
+                Ok(fetched) => return Ok(fetched),
+                Err(failure) => failure,
+            };
+            if attempt >= self.retry.max_retries() || !failure.error.is_retryable() {
+                return Err(failure.error);
+            }
+            let Some(delay) = self.retry.delay(attempt, failure.retry_after) else {
+                error!(
+                    "{url} asked to retry after {:?}, which exceeds the maximum backoff",
+                    failure.retry_after
+                );
+                return Err(failure.error);
+            };
+            attempt += 1;
+            warn!(
+                "request to {url} failed: {}; retrying in {delay:?} (attempt {attempt}/{})",
+                failure.error,
+                self.retry.max_retries()
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    async fn try_fetch(
+        &self,
+        url: &Url,
+        validators: Option<&Validators>,
+        rate_limit: Option<RateLimit>,
+    ) -> Result<Fetched, Failure> {
+        if let Some(rate_limit) = rate_limit {
+            rate_limit.acquire(url).await;
+        }
+        let mut request = self.client.get(url.clone());
+        if let Some(validators) = validators {
+            request = validators.apply(request);
+        }
+        let response = request.send().await.map_err(|err| self.failure(url, err))?;
+        let status = response.status();
+        if status == StatusCode::NOT_MODIFIED && validators.is_some() {
+            return Ok(Fetched::NotModified);
+        }
+        if !status.is_success() {
+            let retry_after = response
+                .headers()
+                .get(RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(retry::parse_retry_after);
+            return Err(Failure {
+                error: self.status_error(url, status),
+                retry_after,
+            });
+        }
+
+        let validators = Validators::from_headers(response.headers());
+        let body = response
+            .text()
+            .await
+            .map_err(|err| self.failure(url, err))?;
+        Ok(Fetched::Modified { body, validators })
+    }
+
+    fn status_error(&self, url: &Url, status: StatusCode) -> ScrapeError {
+        ScrapeError::HttpStatus {
+            provider: self.provider.to_string(),
+            url: Box::new(url.clone()),
+            status: status.as_u16(),
+        }
+    }
+
+    fn failure(&self, url: &Url, error: reqwest::Error) -> Failure {
+        Failure {
+            error: ScrapeError::from_reqwest(self.provider, url, error),
+            retry_after: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::test_server::{Response, TestServer};
+
+    impl HttpClient {
+        /// Get the body of the page at `url`
+        async fn get_text(&self, url: &Url) -> ScrapeResult<String> {
+            match self.fetch(url, None).await? {
+                Fetched::Modified { body, .. } => Ok(body),
+                Fetched::NotModified => unreachable!("unconditional request"),
+            }
+        }
+    }
+
+    fn retrying_client() -> HttpClient {
+        let mut client = HttpClient::new("Test");
+        client.set_retry(
+            RetryPolicy::default()
+                .with_max_retries(3)
+                .with_initial_backoff(Duration::from_millis(10)),
+        );
+        client
+    }
+
+    fn status_error(server: &TestServer, status: u16) -> ScrapeError {
+        ScrapeError::HttpStatus {
+            provider: "Test".to_string(),
+            url: Box::new(server.url()),
+            status,
+        }
+    }
+
+    /// Start a server which replies with `failure` to the first `failures` requests, and then with `200 OK`
+    async fn flaky_server(failures: usize, failure: Response) -> TestServer {
+        let count = Arc::new(AtomicUsize::new(0));
+        TestServer::start(move |_| {
+            if count.fetch_add(1, Ordering::SeqCst) < failures {
+                failure.clone()
+            } else {
+                Response::ok("buongiornissimo")
+            }
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn should_get_text() {
+        let server = TestServer::serve("buongiornissimo").await;
+        assert_eq!(
+            HttpClient::new("Test")
+                .get_text(&server.url())
+                .await
+                .unwrap(),
+            "buongiornissimo"
+        );
+    }
+
+    #[tokio::test]
+    async fn should_return_status_error() {
+        let server = flaky_server(1, Response::status(502)).await;
+        assert_eq!(
+            HttpClient::new("Test")
+                .get_text(&server.url())
+                .await
+                .unwrap_err(),
+            status_error(&server, 502)
+        );
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn should_retry_on_server_errors() {
+        let server = flaky_server(2, Response::status(502)).await;
+        assert_eq!(
+            retrying_client().get_text(&server.url()).await.unwrap(),
+            "buongiornissimo"
+        );
+        assert_eq!(server.requests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn should_give_up_after_max_retries() {
+        let server = flaky_server(10, Response::status(503)).await;
+        assert_eq!(
+            retrying_client().get_text(&server.url()).await.unwrap_err(),
+            status_error(&server, 503)
+        );
+        assert_eq!(server.requests().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn should_not_retry_on_client_errors() {
+        let server = flaky_server(10, Response::status(404)).await;
+        assert_eq!(
+            retrying_client().get_text(&server.url()).await.unwrap_err(),
+            status_error(&server, 404)
+        );
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn should_honor_retry_after() {
+        let server = flaky_server(1, Response::status(429).header("Retry-After", "0")).await;
+        assert_eq!(
+            retrying_client().get_text(&server.url()).await.unwrap(),
+            "buongiornissimo"
+        );
+        assert_eq!(server.requests().len(), 2);
+
+        let server = flaky_server(1, Response::status(429).header("Retry-After", "3600")).await;
+        assert_eq!(
+            retrying_client().get_text(&server.url()).await.unwrap_err(),
+            status_error(&server, 429)
+        );
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn should_retry_on_connection_errors() {
+        let url = Url::parse("http://127.0.0.1:1/").unwrap();
+        let error = retrying_client().get_text(&url).await.unwrap_err();
+        assert!(matches!(error, ScrapeError::Connect { .. }));
+        assert!(error.is_retryable());
+    }
+
+    #[tokio::test]
+    async fn should_apply_rate_limit() {
+        let server = TestServer::serve("buongiornissimo").await;
+        let mut client = HttpClient::new("Test");
+        client.set_rate_limit(RateLimit::new(Duration::from_millis(100)));
+        let started = tokio::time::Instant::now();
+        for _ in 0..3 {
+            client.get_text(&server.url()).await.unwrap();
+        }
+
+        assert!(started.elapsed() >= Duration::from_millis(200));
+        assert_eq!(server.requests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn should_not_request_urls_disallowed_by_robots_txt() {
+        let server = TestServer::start(|request| match request.path.as_str() {
+            "/robots.txt" => Response::ok("User-agent: *\nDisallow: /private/\nCrawl-delay: 0.1"),
+            _ => Response::ok("buongiornissimo"),
+        })
+        .await;
+        let mut client = HttpClient::new("Test");
+        client.set_robots_txt(RobotsTxt::new("buongiornissimo-rs"));
+
+        let disallowed = server.url().join("/private/").unwrap();
+        assert_eq!(
+            client.get_text(&disallowed).await.unwrap_err(),
+            ScrapeError::RobotsDisallowed {
+                provider: "Test".to_string(),
+                url: Box::new(disallowed),
+            }
+        );
+        // crawl delay is applied
+        let started = tokio::time::Instant::now();
+        for _ in 0..2 {
+            assert_eq!(
+                client.get_text(&server.url()).await.unwrap(),
+                "buongiornissimo"
+            );
+        }
+        assert!(started.elapsed() >= Duration::from_millis(100));
+
+        let paths: Vec<String> = server
+            .requests()
+            .into_iter()
+            .map(|request| request.path)
+            .collect();
+        assert_eq!(paths, vec!["/robots.txt", "/", "/"]);
+    }
+
+    /// Start a server which replies with `304 Not Modified` when the request carries the validators of the page
+    async fn conditional_server(
+        etag: Option<&'static str>,
+        last_modified: Option<&'static str>,
+    ) -> TestServer {
+        TestServer::start(move |request| {
+            let matches = match (etag, last_modified) {
+                (Some(etag), _) => request.header("If-None-Match") == Some(etag),
+                (None, Some(last_modified)) => {
+                    request.header("If-Modified-Since") == Some(last_modified)
+                }
+                (None, None) => false,
+            };
+            if matches {
+                return Response::status(304);
+            }
+            let mut response = Response::ok("buongiornissimo");
+            if let Some(etag) = etag {
+                response = response.header("ETag", etag);
+            }
+            if let Some(last_modified) = last_modified {
+                response = response.header("Last-Modified", last_modified);
+            }
+            response
+        })
+        .await
+    }
+
+    fn parse_counting(parsed: &AtomicUsize, body: &str) -> ScrapeResult<Page> {
+        parsed.fetch_add(1, Ordering::SeqCst);
+        assert_eq!(body, "buongiornissimo");
+        Ok(Page::default())
+    }
+
+    #[tokio::test]
+    async fn should_reuse_parsed_page_if_not_modified() {
+        for (etag, last_modified) in [
+            (Some("\"caffe\""), None),
+            (None, Some("Wed, 21 Oct 2015 07:28:00 GMT")),
+        ] {
+            let server = conditional_server(etag, last_modified).await;
+            let client = HttpClient::new("Test");
+            let parsed = AtomicUsize::new(0);
+            for _ in 0..3 {
+                assert_eq!(
+                    client
+                        .get_page(&server.url(), |body| parse_counting(&parsed, body))
+                        .await
+                        .unwrap(),
+                    Page::default()
+                );
+            }
+
+            assert_eq!(parsed.load(Ordering::SeqCst), 1);
+            let requests = server.requests();
+            assert_eq!(requests.len(), 3);
+            assert_eq!(requests[0].header("If-None-Match"), None);
+            assert_eq!(requests[1].header("If-None-Match"), etag);
+            assert_eq!(requests[1].header("If-Modified-Since"), last_modified);
+        }
+    }
+
+    #[tokio::test]
+    async fn should_parse_page_without_validators_every_time() {
+        let server = conditional_server(None, None).await;
+        let client = HttpClient::new("Test");
+        let parsed = AtomicUsize::new(0);
+        for _ in 0..2 {
+            client
+                .get_page(&server.url(), |body| parse_counting(&parsed, body))
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(parsed.load(Ordering::SeqCst), 2);
+        assert!(
+            server
+                .requests()
+                .iter()
+                .all(|request| request.header("If-None-Match").is_none()
+                    && request.header("If-Modified-Since").is_none())
+        );
+    }
+}
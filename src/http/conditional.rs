@@ -0,0 +1,197 @@
+//! # Conditional
+//!
+//! Support for conditional requests, caching the pages parsed by a provider with their `ETag` and `Last-Modified` validators
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use reqwest::RequestBuilder;
+use reqwest::header::{ETAG, HeaderMap, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use url::Url;
+
+use super::super::ScrapedImage;
+
+/// Default maximum amount of pages cached by a [`PageCache`]
+const DEFAULT_CAPACITY: usize = 128;
+
+/// The validators returned by the server with a page, used to ask whether it has been modified since
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub(crate) struct Validators {
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+impl Validators {
+    /// Read the `ETag` and `Last-Modified` headers of a response
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(ToString::to_string)
+        };
+        Self {
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+        }
+    }
+
+    /// Whether the server didn't return any validator, and so the page can't be requested conditionally
+    pub fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+
+    /// Add the `If-None-Match` and `If-Modified-Since` headers to `request`
+    pub fn apply(&self, mut request: RequestBuilder) -> RequestBuilder {
+        if let Some(etag) = &self.etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &self.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, last_modified);
+        }
+        request
+    }
+}
+
+/// A page parsed by a provider
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct Page {
+    /// The images found in the page
+    pub images: Vec<ScrapedImage>,
+    /// The url of the following page, if any
+    pub next: Option<Url>,
+}
+
+/// A parsed page, with the validators it has been returned with
+#[derive(Debug, Clone)]
+struct CachedPage {
+    validators: Validators,
+    page: Page,
+    last_access: Instant,
+}
+
+/// The pages parsed by a provider, with their validators.
+///
+/// When the cache is full, the least recently used page is evicted.
+/// The cache is shared by the clones of the client.
+#[derive(Debug, Clone)]
+pub(crate) struct PageCache {
+    pages: Arc<Mutex<HashMap<Url, CachedPage>>>,
+    capacity: usize,
+}
+
+impl Default for PageCache {
+    fn default() -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+}
+
+impl PageCache {
+    /// Create a new [`PageCache`] which keeps at most `capacity` pages
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            pages: Arc::default(),
+            capacity,
+        }
+    }
+
+    /// Get the cached page at `url`, with its validators
+    pub fn get(&self, url: &Url) -> Option<(Validators, Page)> {
+        let mut pages = self.pages.lock().expect("page cache lock poisoned");
+        let cached = pages.get_mut(url)?;
+        cached.last_access = Instant::now();
+        Some((cached.validators.clone(), cached.page.clone()))
+    }
+
+    /// Cache the page at `url`; pages without validators are removed, since they can't be requested conditionally
+    pub fn insert(&self, url: &Url, validators: Validators, page: Page) {
+        let mut pages = self.pages.lock().expect("page cache lock poisoned");
+        if validators.is_empty() || self.capacity == 0 {
+            pages.remove(url);
+            return;
+        }
+        if !pages.contains_key(url) && pages.len() >= self.capacity {
+            let least_recently_used = pages
+                .iter()
+                .min_by_key(|(_, cached)| cached.last_access)
+                .map(|(url, _)| url.clone());
+            if let Some(evicted) = least_recently_used {
+                debug!("page cache is full; evicting {evicted}");
+                pages.remove(&evicted);
+            }
+        }
+        pages.insert(
+            url.clone(),
+            CachedPage {
+                validators,
+                page,
+                last_access: Instant::now(),
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+    use reqwest::header::HeaderValue;
+
+    use super::*;
+
+    #[test]
+    fn should_read_validators_from_headers() {
+        let mut headers = HeaderMap::new();
+        assert!(Validators::from_headers(&headers).is_empty());
+
+        headers.insert(ETAG, HeaderValue::from_static("\"abc\""));
+        headers.insert(
+            LAST_MODIFIED,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(
+            Validators::from_headers(&headers),
+            Validators {
+                etag: Some("\"abc\"".to_string()),
+                last_modified: Some("Wed, 21 Oct 2015 07:28:00 GMT".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn should_cache_only_pages_with_validators() {
+        let cache = PageCache::default();
+        let url = Url::parse("https://example.com/buongiorno/").unwrap();
+        let validators = Validators {
+            etag: Some("\"abc\"".to_string()),
+            last_modified: None,
+        };
+        cache.insert(&url, validators.clone(), Page::default());
+        assert_eq!(cache.get(&url), Some((validators, Page::default())));
+
+        cache.insert(&url, Validators::default(), Page::default());
+        assert_eq!(cache.get(&url), None);
+    }
+
+    #[test]
+    fn should_evict_least_recently_used_page() {
+        let cache = PageCache::with_capacity(2);
+        let validators = Validators {
+            etag: Some("\"abc\"".to_string()),
+            last_modified: None,
+        };
+        let url = |n: usize| Url::parse(&format!("https://example.com/page/{n}/")).unwrap();
+        cache.insert(&url(1), validators.clone(), Page::default());
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        cache.insert(&url(2), validators.clone(), Page::default());
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        assert!(cache.get(&url(1)).is_some());
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        cache.insert(&url(3), validators, Page::default());
+
+        assert!(cache.get(&url(1)).is_some());
+        assert!(cache.get(&url(2)).is_none());
+        assert!(cache.get(&url(3)).is_some());
+    }
+}
@@ -0,0 +1,198 @@
+//! # Rate limit
+//!
+//! A per-host rate limiter, shared by all the providers
+
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+
+use tokio::time::Instant;
+use url::Url;
+
+/// The last request scheduled for each host, shared by all the providers
+static HOSTS: LazyLock<Mutex<HashMap<String, Slot>>> = LazyLock::new(Default::default);
+
+/// The last request scheduled for a host
+#[derive(Debug, Copy, Clone)]
+struct Slot {
+    /// When the last request is sent
+    at: Instant,
+    /// The longest interval requested for the host
+    interval: Duration,
+}
+
+impl Slot {
+    /// Whether the slot doesn't delay any further request, so that it can be forgotten
+    fn is_expired(&self, now: Instant) -> bool {
+        self.at + self.interval <= now
+    }
+}
+
+/// Describes the minimum interval between two requests sent to the same host.
+///
+/// The state of the limiter is shared by all the providers, so that different provider instances
+/// (or different providers scraping the same site) don't exceed the limit together.
+/// Requests which would exceed the limit are queued and sent in order as soon as they're allowed.
+/// Hosts are forgotten once they've been idle for the longest interval requested for them.
+///
+/// ```rust
+/// use std::time::Duration;
+///
+/// use buongiornissimo_rs::{Augurando, RateLimit};
+///
+/// let provider = Augurando::default().with_rate_limit(RateLimit::per_minute(20));
+/// let same_site = Augurando::default().with_rate_limit(RateLimit::new(Duration::from_secs(3)));
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct RateLimit {
+    min_interval: Duration,
+}
+
+impl RateLimit {
+    /// Wait at least `min_interval` between two requests to the same host
+    pub fn new(min_interval: Duration) -> Self {
+        Self { min_interval }
+    }
+
+    /// Send at most `requests` requests per minute to the same host.
+    ///
+    /// A value of `0` is considered as `1`.
+    pub fn per_minute(requests: u32) -> Self {
+        Self::new(Duration::from_secs(60) / requests.max(1))
+    }
+
+    /// Get the minimum interval between two requests to the same host
+    pub fn min_interval(&self) -> Duration {
+        self.min_interval
+    }
+
+    /// Wait until a request to the host of `url` is allowed by the limit
+    pub(crate) async fn acquire(&self, url: &Url) {
+        let allowed_at = self.schedule(Self::host_key(url), Instant::now());
+        let now = Instant::now();
+        if allowed_at > now {
+            debug!("rate limiting request to {url} for {:?}", allowed_at - now);
+            tokio::time::sleep_until(allowed_at).await;
+        }
+    }
+
+    /// Reserve the first slot allowed for a request to `host`, so that concurrent requests are queued, and return it.
+    ///
+    /// The expired hosts are forgotten meanwhile.
+    fn schedule(&self, host: String, now: Instant) -> Instant {
+        let mut hosts = HOSTS.lock().expect("rate limiter lock poisoned");
+        hosts.retain(|_, slot| !slot.is_expired(now));
+        let slot = match hosts.get(&host) {
+            Some(last) => Slot {
+                at: (last.at + self.min_interval).max(now),
+                interval: last.interval.max(self.min_interval),
+            },
+            None => Slot {
+                at: now,
+                interval: self.min_interval,
+            },
+        };
+        hosts.insert(host, slot);
+        slot.at
+    }
+
+    fn host_key(url: &Url) -> String {
+        format!(
+            "{}:{}",
+            url.host_str().unwrap_or_default(),
+            url.port_or_known_default().unwrap_or_default()
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_build_rate_limit() {
+        assert_eq!(
+            RateLimit::per_minute(30).min_interval(),
+            Duration::from_secs(2)
+        );
+        assert_eq!(
+            RateLimit::per_minute(0).min_interval(),
+            Duration::from_secs(60)
+        );
+        assert_eq!(
+            RateLimit::new(Duration::from_millis(500)).min_interval(),
+            Duration::from_millis(500)
+        );
+    }
+
+    #[test]
+    fn should_get_host_key() {
+        assert_eq!(
+            RateLimit::host_key(&Url::parse("https://augurando.it/buongiorno/").unwrap()),
+            "augurando.it:443"
+        );
+        assert_eq!(
+            RateLimit::host_key(&Url::parse("http://127.0.0.1:8080/").unwrap()),
+            "127.0.0.1:8080"
+        );
+    }
+
+    #[tokio::test]
+    async fn should_queue_requests_to_the_same_host() {
+        let limit = RateLimit::new(Duration::from_millis(100));
+        let url = Url::parse("https://rate-limit-same-host.test/").unwrap();
+        let started = Instant::now();
+        futures_util::future::join_all((0..3).map(|_| limit.acquire(&url))).await;
+
+        assert!(started.elapsed() >= Duration::from_millis(200));
+    }
+
+    #[test]
+    fn should_forget_idle_hosts() {
+        let limit = RateLimit::new(Duration::from_millis(100));
+        let host = "rate-limit-idle.test:443".to_string();
+        let now = Instant::now();
+        assert_eq!(limit.schedule(host.clone(), now), now);
+        assert_eq!(
+            limit.schedule(host.clone(), now),
+            now + Duration::from_millis(100)
+        );
+        assert!(HOSTS.lock().unwrap().contains_key(&host));
+
+        // scheduling any request forgets the expired hosts
+        let later = now + Duration::from_millis(200);
+        RateLimit::new(Duration::ZERO).schedule("rate-limit-other.test:443".to_string(), later);
+        assert!(!HOSTS.lock().unwrap().contains_key(&host));
+    }
+
+    #[tokio::test]
+    async fn should_not_limit_different_hosts() {
+        let limit = RateLimit::new(Duration::from_secs(10));
+        let started = Instant::now();
+        limit
+            .acquire(&Url::parse("https://rate-limit-first.test/").unwrap())
+            .await;
+        limit
+            .acquire(&Url::parse("https://rate-limit-second.test/").unwrap())
+            .await;
+
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn should_share_limit_between_limiters() {
+        let url = Url::parse("https://rate-limit-shared.test/").unwrap();
+        let started = Instant::now();
+        RateLimit::new(Duration::from_millis(100))
+            .acquire(&url)
+            .await;
+        RateLimit::new(Duration::from_millis(100))
+            .acquire(&url)
+            .await;
+
+        assert!(started.elapsed() >= Duration::from_millis(100));
+    }
+}
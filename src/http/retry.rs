@@ -0,0 +1,178 @@
+//! # Retry
+//!
+//! The policy used to retry the requests which failed for a transient error
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use rand::Rng;
+
+/// Describes how the requests to a provider are retried when they fail for a transient error.
+///
+/// Only retryable errors are retried (see [`crate::ScrapeError::is_retryable`]): connection errors, timeouts,
+/// `429 Too Many Requests` and `5xx` statuses.
+/// The delay between the attempts grows exponentially, starting from the initial backoff up to the maximum backoff,
+/// and a random jitter is applied so that concurrent clients don't retry all at the same time.
+/// If the provider replies with a `Retry-After` header, its value is used as delay instead,
+/// unless it exceeds the maximum backoff, in which case the request is not retried at all.
+///
+/// ```rust
+/// use std::time::Duration;
+///
+/// use buongiornissimo_rs::{RetryPolicy, TiCondivido};
+///
+/// let provider = TiCondivido::default().with_retry(
+///     RetryPolicy::default()
+///         .with_max_retries(5)
+///         .with_initial_backoff(Duration::from_secs(1)),
+/// );
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct RetryPolicy {
+    max_retries: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    /// 3 retries, with backoff starting from 500ms up to 30 seconds, and jitter
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            jitter: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy which never retries. This is the policy used by default by the providers.
+    pub fn none() -> Self {
+        Self {
+            max_retries: 0,
+            ..Default::default()
+        }
+    }
+
+    /// Set the maximum amount of retries after the first attempt
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Set the delay before the first retry, which is then doubled at each attempt
+    pub fn with_initial_backoff(mut self, initial_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self
+    }
+
+    /// Set the maximum delay between two attempts
+    pub fn with_max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Set whether to apply a random jitter to the delays. If enabled, each delay is randomly picked between half and the full backoff.
+    pub fn with_jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Get the maximum amount of retries after the first attempt
+    pub fn max_retries(&self) -> u32 {
+        self.max_retries
+    }
+
+    /// Get the delay to wait before the retry number `attempt` (starting from 0).
+    ///
+    /// Returns `None` if the request must not be retried, because the provider asked to wait more than the maximum backoff.
+    pub(crate) fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Option<Duration> {
+        if let Some(retry_after) = retry_after {
+            return (retry_after <= self.max_backoff).then_some(retry_after);
+        }
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_backoff);
+        if self.jitter && !backoff.is_zero() {
+            Some(rand::rng().random_range(backoff / 2..=backoff))
+        } else {
+            Some(backoff)
+        }
+    }
+}
+
+/// Parse the value of a `Retry-After` header, which can be either an amount of seconds or a http date
+pub(crate) fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        date.with_timezone(&Utc)
+            .signed_duration_since(Utc::now())
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn should_not_retry_by_default() {
+        assert_eq!(RetryPolicy::none().max_retries(), 0);
+        assert_eq!(RetryPolicy::default().max_retries(), 3);
+    }
+
+    #[test]
+    fn should_grow_backoff_exponentially() {
+        let policy = RetryPolicy::default()
+            .with_initial_backoff(Duration::from_millis(100))
+            .with_max_backoff(Duration::from_millis(500))
+            .with_jitter(false);
+        assert_eq!(policy.delay(0, None), Some(Duration::from_millis(100)));
+        assert_eq!(policy.delay(1, None), Some(Duration::from_millis(200)));
+        assert_eq!(policy.delay(2, None), Some(Duration::from_millis(400)));
+        assert_eq!(policy.delay(3, None), Some(Duration::from_millis(500)));
+        assert_eq!(policy.delay(64, None), Some(Duration::from_millis(500)));
+    }
+
+    #[test]
+    fn should_apply_jitter() {
+        let policy = RetryPolicy::default().with_initial_backoff(Duration::from_millis(100));
+        for _ in 0..100 {
+            let delay = policy.delay(1, None).unwrap();
+            assert!(delay >= Duration::from_millis(100) && delay <= Duration::from_millis(200));
+        }
+    }
+
+    #[test]
+    fn should_honor_retry_after() {
+        let policy = RetryPolicy::default().with_max_backoff(Duration::from_secs(10));
+        assert_eq!(
+            policy.delay(0, Some(Duration::from_secs(5))),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(policy.delay(0, Some(Duration::from_secs(60))), None);
+    }
+
+    #[test]
+    fn should_parse_retry_after() {
+        assert_eq!(parse_retry_after(" 120 "), Some(Duration::from_secs(120)));
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+            Some(Duration::ZERO)
+        );
+        let date = (Utc::now() + chrono::Duration::seconds(3600)).to_rfc2822();
+        assert!(parse_retry_after(&date).unwrap() > Duration::from_secs(3500));
+        assert_eq!(parse_retry_after("tomorrow"), None);
+    }
+}
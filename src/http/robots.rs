@@ -0,0 +1,382 @@
+//! # Robots
+//!
+//! Support for the `robots.txt` exclusion protocol, with a per-host cache shared by all the providers
+
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::Duration;
+
+use reqwest::Client;
+use tokio::time::Instant;
+use url::Url;
+
+use super::super::{ScrapeError, ScrapeResult};
+
+/// A fetched `robots.txt`, with the instant it has been fetched at
+type CachedRobots = (Instant, Arc<Robots>);
+
+/// The `robots.txt` fetched for each origin
+static CACHE: LazyLock<Mutex<HashMap<String, CachedRobots>>> = LazyLock::new(Default::default);
+
+/// Describes how the providers comply with the `robots.txt` of the sites they scrape.
+///
+/// When enabled, the `robots.txt` of each host is fetched before the first request and cached;
+/// urls disallowed for the configured user agent are not requested and [`ScrapeError::RobotsDisallowed`] is returned instead,
+/// while the `Crawl-delay` is applied as minimum interval between the requests to the host.
+///
+/// The user agent should match the product token of the `User-Agent` header sent by the [`Client`].
+///
+/// ```rust
+/// use buongiornissimo_rs::{BuongiornissimoCaffe, RobotsTxt};
+///
+/// let provider = BuongiornissimoCaffe::default().with_robots_txt(RobotsTxt::new("buongiornissimo-rs"));
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RobotsTxt {
+    user_agent: String,
+    ttl: Duration,
+}
+
+impl RobotsTxt {
+    /// Comply with the rules for `user_agent`, caching each `robots.txt` for 24 hours
+    pub fn new(user_agent: impl Into<String>) -> Self {
+        Self {
+            user_agent: user_agent.into(),
+            ttl: Duration::from_secs(24 * 60 * 60),
+        }
+    }
+
+    /// Set for how long a fetched `robots.txt` is cached
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Get the user agent the rules are applied for
+    pub fn user_agent(&self) -> &str {
+        &self.user_agent
+    }
+
+    /// Check whether `url` can be requested by `provider`.
+    ///
+    /// Returns the crawl delay for the host if allowed, or [`ScrapeError::RobotsDisallowed`] otherwise.
+    pub(crate) async fn check(
+        &self,
+        client: &Client,
+        provider: &str,
+        url: &Url,
+    ) -> ScrapeResult<Option<Duration>> {
+        let robots = self.robots(client, provider, url).await?;
+        let group = robots.group(&self.user_agent);
+        let path = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        };
+        if !group.is_none_or(|group| group.is_allowed(&path)) {
+            error!("{url} is disallowed by robots.txt for {}", self.user_agent);
+            return Err(ScrapeError::RobotsDisallowed {
+                provider: provider.to_string(),
+                url: Box::new(url.clone()),
+            });
+        }
+
+        Ok(group.and_then(|group| group.crawl_delay))
+    }
+
+    /// Get the robots of the host of `url`, from the cache or fetching them
+    async fn robots(
+        &self,
+        client: &Client,
+        provider: &str,
+        url: &Url,
+    ) -> ScrapeResult<Arc<Robots>> {
+        let origin = url.origin().ascii_serialization();
+        if let Some((fetched_at, robots)) = CACHE
+            .lock()
+            .expect("robots cache lock poisoned")
+            .get(&origin)
+            && fetched_at.elapsed() < self.ttl
+        {
+            return Ok(robots.clone());
+        }
+
+        let robots_url = url.join("/robots.txt").expect("invalid robots.txt url");
+        debug!("fetching {robots_url}");
+        let response = client
+            .get(robots_url.clone())
+            .send()
+            .await
+            .map_err(|err| ScrapeError::from_reqwest(provider, &robots_url, err))?;
+        let status = response.status();
+        let robots = if status.is_success() {
+            let body = response
+                .text()
+                .await
+                .map_err(|err| ScrapeError::from_reqwest(provider, &robots_url, err))?;
+            Robots::parse(&body)
+        } else if status.is_client_error() {
+            // a missing robots.txt allows everything
+            debug!("{robots_url} replied with status {status}; everything is allowed");
+            Robots::default()
+        } else {
+            return Err(ScrapeError::HttpStatus {
+                provider: provider.to_string(),
+                url: Box::new(robots_url),
+                status: status.as_u16(),
+            });
+        };
+
+        let robots = Arc::new(robots);
+        CACHE
+            .lock()
+            .expect("robots cache lock poisoned")
+            .insert(origin, (Instant::now(), robots.clone()));
+
+        Ok(robots)
+    }
+}
+
+/// A parsed `robots.txt`
+#[derive(Debug, Default, Clone, PartialEq)]
+struct Robots {
+    groups: Vec<Group>,
+}
+
+/// A group of rules of a `robots.txt`, applied to the listed user agents
+#[derive(Debug, Default, Clone, PartialEq)]
+struct Group {
+    user_agents: Vec<String>,
+    rules: Vec<Rule>,
+    crawl_delay: Option<Duration>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Rule {
+    allow: bool,
+    pattern: String,
+}
+
+impl Robots {
+    fn parse(robots: &str) -> Self {
+        let mut groups: Vec<Group> = Vec::new();
+        // whether the last line was a user-agent line, so that consecutive user agents share the group
+        let mut reading_agents = false;
+
+        for line in robots.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let key = key.trim().to_ascii_lowercase();
+            let value = value.trim();
+
+            if key == "user-agent" {
+                if !reading_agents {
+                    groups.push(Group::default());
+                }
+                reading_agents = true;
+                if let Some(group) = groups.last_mut() {
+                    group.user_agents.push(value.to_ascii_lowercase());
+                }
+                continue;
+            }
+            reading_agents = false;
+            let Some(group) = groups.last_mut() else {
+                continue;
+            };
+            match key.as_str() {
+                "allow" | "disallow" if !value.is_empty() => group.rules.push(Rule {
+                    allow: key == "allow",
+                    pattern: value.to_string(),
+                }),
+                "crawl-delay" => {
+                    group.crawl_delay = value
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|delay| delay.is_finite() && *delay >= 0.0)
+                        .map(Duration::from_secs_f64);
+                }
+                _ => {}
+            }
+        }
+
+        Self { groups }
+    }
+
+    /// Get the group which applies to `user_agent`, falling back to the `*` group
+    fn group(&self, user_agent: &str) -> Option<&Group> {
+        let user_agent = user_agent.to_ascii_lowercase();
+        self.groups
+            .iter()
+            .find(|group| group.user_agents.contains(&user_agent))
+            .or_else(|| {
+                self.groups
+                    .iter()
+                    .find(|group| group.user_agents.iter().any(|agent| agent == "*"))
+            })
+    }
+}
+
+impl Group {
+    /// Whether `path` is allowed; the most specific rule wins and `Allow` wins on tie
+    fn is_allowed(&self, path: &str) -> bool {
+        self.rules
+            .iter()
+            .filter(|rule| Self::matches(&rule.pattern, path))
+            .max_by_key(|rule| (rule.pattern.len(), rule.allow))
+            .is_none_or(|rule| rule.allow)
+    }
+
+    /// Match `path` against a rule pattern, which supports the `*` wildcard and the `$` end anchor
+    fn matches(pattern: &str, path: &str) -> bool {
+        let (pattern, anchored) = match pattern.strip_suffix('$') {
+            Some(pattern) => (pattern, true),
+            None => (pattern, false),
+        };
+        let mut parts = pattern.split('*');
+        let first = parts.next().unwrap_or_default();
+        let Some(mut rest) = path.strip_prefix(first) else {
+            return false;
+        };
+        let parts: Vec<&str> = parts.collect();
+        for (i, part) in parts.iter().enumerate() {
+            let is_last = i + 1 == parts.len();
+            if is_last && anchored {
+                return rest.ends_with(part);
+            }
+            match rest.find(part) {
+                Some(index) => rest = &rest[index + part.len()..],
+                None => return false,
+            }
+        }
+
+        !anchored || rest.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::test_server::{Response, TestServer};
+
+    const ROBOTS_TXT: &str = r#"
+# robots.txt
+User-agent: *
+Disallow: /wp-admin/
+Allow: /wp-admin/admin-ajax.php
+
+User-agent: BadBot
+User-agent: buongiornissimo-rs
+Disallow: /category/buon-natale/
+Disallow: /*?s=
+Crawl-delay: 2.5
+
+User-agent: Googlebot
+Disallow:
+"#;
+
+    #[test]
+    fn should_parse_robots_txt() {
+        let robots = Robots::parse(ROBOTS_TXT);
+        assert_eq!(robots.groups.len(), 3);
+        let group = robots.group("Buongiornissimo-RS").unwrap();
+        assert_eq!(group.user_agents, vec!["badbot", "buongiornissimo-rs"]);
+        assert_eq!(group.crawl_delay, Some(Duration::from_millis(2500)));
+        assert_eq!(group.rules.len(), 2);
+        assert!(robots.group("googlebot").unwrap().rules.is_empty());
+        assert_eq!(
+            robots.group("other").unwrap().user_agents,
+            vec!["*".to_string()]
+        );
+    }
+
+    #[test]
+    fn should_tell_whether_path_is_allowed() {
+        let robots = Robots::parse(ROBOTS_TXT);
+        let group = robots.group("buongiornissimo-rs").unwrap();
+        assert!(group.is_allowed("/category/immagini-buongiorno/"));
+        assert!(!group.is_allowed("/category/buon-natale/"));
+        assert!(!group.is_allowed("/category/buon-natale/page/2/"));
+        assert!(!group.is_allowed("/search?s=buongiorno"));
+        // rules of other groups are not applied
+        assert!(group.is_allowed("/wp-admin/"));
+
+        let group = robots.group("other").unwrap();
+        assert!(!group.is_allowed("/wp-admin/options.php"));
+        assert!(group.is_allowed("/wp-admin/admin-ajax.php"));
+        assert!(group.is_allowed("/category/buon-natale/"));
+    }
+
+    #[test]
+    fn should_match_patterns() {
+        assert!(Group::matches("/", "/anything"));
+        assert!(Group::matches("/*.jpg$", "/images/a.jpg"));
+        assert!(!Group::matches("/*.jpg$", "/images/a.jpg?size=large"));
+        assert!(Group::matches("/page$", "/page"));
+        assert!(!Group::matches("/page$", "/page/2"));
+        assert!(Group::matches("/a*b*c", "/a-x-b-y-c-z"));
+        assert!(!Group::matches("/a*b*c", "/a-x-c-y-b"));
+    }
+
+    #[tokio::test]
+    async fn should_check_url_against_robots_txt() {
+        let server = TestServer::start(|request| match request.path.as_str() {
+            "/robots.txt" => Response::ok(ROBOTS_TXT),
+            _ => Response::ok("buongiornissimo"),
+        })
+        .await;
+        let robots = RobotsTxt::new("buongiornissimo-rs");
+        let client = Client::default();
+
+        assert_eq!(
+            robots
+                .check(
+                    &client,
+                    "Test",
+                    &server.url().join("/category/buongiorno/").unwrap()
+                )
+                .await
+                .unwrap(),
+            Some(Duration::from_millis(2500))
+        );
+        let disallowed = server.url().join("/category/buon-natale/").unwrap();
+        assert_eq!(
+            robots
+                .check(&client, "Test", &disallowed)
+                .await
+                .unwrap_err(),
+            ScrapeError::RobotsDisallowed {
+                provider: "Test".to_string(),
+                url: Box::new(disallowed)
+            }
+        );
+        // robots.txt is cached
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn should_allow_everything_without_robots_txt() {
+        let server = TestServer::start(|_| Response::status(404)).await;
+        assert_eq!(
+            RobotsTxt::new("buongiornissimo-rs")
+                .check(&Client::default(), "Test", &server.url())
+                .await
+                .unwrap(),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn should_fail_if_robots_txt_is_unavailable() {
+        let server = TestServer::start(|_| Response::status(503)).await;
+        let error = RobotsTxt::new("buongiornissimo-rs")
+            .check(&Client::default(), "Test", &server.url())
+            .await
+            .unwrap_err();
+        assert_eq!(error.status(), Some(503));
+        assert_eq!(error.url().unwrap().path(), "/robots.txt");
+    }
+}
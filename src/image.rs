@@ -0,0 +1,186 @@
+//! # Image
+//!
+//! This module exposes the types describing the images scraped from the providers
+
+mod resolver;
+
+pub(crate) use self::resolver::SourcePolicy;
+
+use scraper::ElementRef;
+use url::Url;
+
+/// An image scraped from a provider page, with the metadata found on its `<img>` element
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScrapedImage {
+    /// Url of the image
+    pub url: Url,
+    /// Alternative text of the image (`alt` attribute)
+    pub alt: Option<String>,
+    /// Width of the image in pixels, as declared by the `width` attribute
+    pub width: Option<u32>,
+    /// Height of the image in pixels, as declared by the `height` attribute
+    pub height: Option<u32>,
+    /// Candidates declared by the srcsets of the image (`srcset`, `data-srcset` and `<picture>` sources)
+    pub srcset: Vec<SrcsetCandidate>,
+    /// Url of the page the image has been found in
+    pub page_url: Url,
+    /// Name of the provider which found the image
+    pub provider: String,
+}
+
+/// An image candidate declared in a `srcset` attribute
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SrcsetCandidate {
+    /// Url of the candidate
+    pub url: Url,
+    /// The descriptor of the candidate, such as `800w` or `2x`
+    pub descriptor: Option<String>,
+}
+
+impl ScrapedImage {
+    /// Create a new [`ScrapedImage`] without any metadata
+    pub fn new(url: Url, page_url: Url, provider: impl Into<String>) -> Self {
+        Self {
+            url,
+            alt: None,
+            width: None,
+            height: None,
+            srcset: Vec::new(),
+            page_url,
+            provider: provider.into(),
+        }
+    }
+
+    /// Build a [`ScrapedImage`] from an `<img>` element, picking the largest source available.
+    ///
+    /// Relative sources are resolved against the `page_url`.
+    /// Returns `None` if the element has no http source, or if it's not allowed by the `policy`.
+    pub(crate) fn from_element(
+        element: ElementRef,
+        policy: SourcePolicy,
+        page_url: &Url,
+        provider: &str,
+    ) -> Option<Self> {
+        let source = resolver::resolve(element, policy, page_url)?;
+        let element = element.value();
+
+        Some(Self {
+            url: source.url,
+            alt: element
+                .attr("alt")
+                .map(str::trim)
+                .filter(|alt| !alt.is_empty())
+                .map(str::to_string),
+            width: element.attr("width").and_then(|w| w.trim().parse().ok()),
+            height: element.attr("height").and_then(|h| h.trim().parse().ok()),
+            srcset: source.srcset,
+            page_url: page_url.clone(),
+            provider: provider.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+    use scraper::{Html, Selector};
+
+    use super::*;
+
+    fn scrape_first_image(html: &str) -> Option<ScrapedImage> {
+        let document = Html::parse_fragment(html);
+        let selector = Selector::parse("img").unwrap();
+        let element = document.select(&selector).next().unwrap();
+        ScrapedImage::from_element(
+            element,
+            SourcePolicy::Any,
+            &Url::parse("https://example.com/page/").unwrap(),
+            "Example",
+        )
+    }
+
+    #[test]
+    fn should_build_image_from_element() {
+        let image = scrape_first_image(
+            r#"<img src="https://example.com/a.jpg" alt=" Buongiorno " width="800" height="600" srcset="https://example.com/a-300.jpg 300w, https://example.com/a.jpg 800w">"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            image,
+            ScrapedImage {
+                url: Url::parse("https://example.com/a.jpg").unwrap(),
+                alt: Some("Buongiorno".to_string()),
+                width: Some(800),
+                height: Some(600),
+                srcset: vec![
+                    SrcsetCandidate {
+                        url: Url::parse("https://example.com/a-300.jpg").unwrap(),
+                        descriptor: Some("300w".to_string()),
+                    },
+                    SrcsetCandidate {
+                        url: Url::parse("https://example.com/a.jpg").unwrap(),
+                        descriptor: Some("800w".to_string()),
+                    },
+                ],
+                page_url: Url::parse("https://example.com/page/").unwrap(),
+                provider: "Example".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn should_serialize_image() {
+        let image = scrape_first_image(
+            r#"<img src="https://example.com/a.jpg" alt="Buongiorno" srcset="https://example.com/a-300.jpg 300w">"#,
+        )
+        .unwrap();
+        let json = serde_json::to_value(&image).unwrap();
+        assert_eq!(json["url"], image.url.as_str());
+        assert_eq!(json["srcset"][0]["descriptor"], "300w");
+        assert_eq!(json["provider"], "Example");
+        assert_eq!(serde_json::from_value::<ScrapedImage>(json).unwrap(), image);
+    }
+
+    #[test]
+    fn should_build_image_without_metadata() {
+        let image =
+            scrape_first_image(r#"<img data-src="https://example.com/a.jpg" alt="" width="auto">"#)
+                .unwrap();
+
+        assert_eq!(
+            image,
+            ScrapedImage::new(
+                Url::parse("https://example.com/a.jpg").unwrap(),
+                Url::parse("https://example.com/page/").unwrap(),
+                "Example"
+            )
+        );
+    }
+
+    #[test]
+    fn should_not_build_image_without_http_source() {
+        assert!(scrape_first_image(r#"<img src="data:image/gif;base64,R0lGOD">"#).is_none());
+    }
+
+    #[test]
+    fn should_build_image_with_relative_sources() {
+        let image = scrape_first_image(
+            r#"<img src="/wp-content/uploads/a.jpg" width="800" srcset="//cdn.example.com/a-300.jpg 300w">"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            image.url.as_str(),
+            "https://example.com/wp-content/uploads/a.jpg"
+        );
+        assert_eq!(
+            image.srcset[0].url.as_str(),
+            "https://cdn.example.com/a-300.jpg"
+        );
+    }
+}
@@ -0,0 +1,396 @@
+//! # Resolver
+//!
+//! Resolves the source of an `<img>` element, looking at all the attributes used by lazy-loading libraries
+//! and responsive images, in order to pick the full-resolution image.
+
+use scraper::ElementRef;
+use url::Url;
+
+use super::SrcsetCandidate;
+
+/// Attributes which may contain the url of the image, sorted by priority.
+///
+/// Lazy-loading attributes come first, since `src` usually contains a placeholder or a thumbnail.
+const SRC_ATTRIBUTES: &[&str] = &["data-lazy-src", "data-original", "data-src", "src"];
+
+/// Attributes which may contain a srcset
+const SRCSET_ATTRIBUTES: &[&str] = &["srcset", "data-srcset", "data-lazy-srcset"];
+
+/// Attributes set by lazy-loading libraries
+const LAZY_ATTRIBUTES: &[&str] = &[
+    "data-lazy-src",
+    "data-original",
+    "data-src",
+    "data-srcset",
+    "data-lazy-srcset",
+];
+
+/// Describes which `<img>` elements are considered by the resolver
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum SourcePolicy {
+    /// Every image is considered
+    Any,
+    /// Only lazy-loaded images are considered.
+    /// This is used on sites where the actual contents are lazy-loaded, while icons and decorations are not.
+    LazyOnly,
+}
+
+/// The sources resolved for an `<img>` element
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ResolvedSource {
+    /// The url of the largest image available
+    pub url: Url,
+    /// All the candidates declared by the srcsets of the element
+    pub srcset: Vec<SrcsetCandidate>,
+}
+
+/// Resolve the sources of the provided `<img>` element.
+///
+/// The candidates are collected from the lazy-loading attributes (`data-lazy-src`, `data-original`, `data-src`),
+/// from `src`, from the srcsets (`srcset`, `data-srcset`) and from the `<source>` elements of the parent `<picture>`.
+/// Then the largest candidate is picked; in case of a tie, the first one in the order above is preferred.
+///
+/// Relative and protocol-relative sources (such as `/wp-content/a.jpg` or `//cdn.example.com/a.jpg`)
+/// are resolved against the `page_url`.
+///
+/// Returns `None` if the element has no http source, or if it is not lazy-loaded with [`SourcePolicy::LazyOnly`].
+pub fn resolve(
+    element: ElementRef,
+    policy: SourcePolicy,
+    page_url: &Url,
+) -> Option<ResolvedSource> {
+    let img = element.value();
+    if policy == SourcePolicy::LazyOnly
+        && !LAZY_ATTRIBUTES.iter().any(|attr| img.attr(attr).is_some())
+    {
+        return None;
+    }
+    let declared_width = img.attr("width").and_then(|w| w.trim().parse::<u32>().ok());
+
+    let mut srcset = Vec::new();
+    for attr in SRCSET_ATTRIBUTES {
+        if let Some(value) = img.attr(attr) {
+            srcset.extend(parse_srcset(value, page_url));
+        }
+    }
+    srcset.extend(picture_sources(element, page_url));
+
+    let sources = SRC_ATTRIBUTES
+        .iter()
+        .filter_map(|attr| img.attr(attr))
+        .filter_map(|src| parse_http_url(src, page_url))
+        .map(|url| (url, declared_width.unwrap_or_default() as f64));
+    let candidates = srcset
+        .iter()
+        .map(|candidate| (candidate.url.clone(), score(candidate, declared_width)));
+
+    let mut best: Option<(Url, f64)> = None;
+    for (url, score) in sources.chain(candidates) {
+        if best
+            .as_ref()
+            .is_none_or(|(_, best_score)| score > *best_score)
+        {
+            best = Some((url, score));
+        }
+    }
+
+    best.map(|(url, _)| ResolvedSource { url, srcset })
+}
+
+/// Collect the srcset candidates of the `<source>` elements, if the image is inside of a `<picture>`
+fn picture_sources(element: ElementRef, page_url: &Url) -> Vec<SrcsetCandidate> {
+    let Some(picture) = element
+        .parent()
+        .and_then(ElementRef::wrap)
+        .filter(|parent| parent.value().name() == "picture")
+    else {
+        return Vec::new();
+    };
+
+    picture
+        .children()
+        .filter_map(ElementRef::wrap)
+        .filter(|child| child.value().name() == "source")
+        .flat_map(|source| {
+            SRCSET_ATTRIBUTES
+                .iter()
+                .filter_map(|attr| source.value().attr(attr))
+                .flat_map(|srcset| parse_srcset(srcset, page_url))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Get the size score of a srcset candidate.
+///
+/// Width descriptors are used as they are, while pixel density descriptors are multiplied by the declared width of the image.
+fn score(candidate: &SrcsetCandidate, declared_width: Option<u32>) -> f64 {
+    let width = declared_width.unwrap_or(1) as f64;
+    match candidate.descriptor.as_deref() {
+        Some(descriptor) if descriptor.ends_with('w') => descriptor
+            .trim_end_matches('w')
+            .parse::<f64>()
+            .unwrap_or_default(),
+        Some(descriptor) if descriptor.ends_with('x') => {
+            descriptor
+                .trim_end_matches('x')
+                .parse::<f64>()
+                .unwrap_or_default()
+                * width
+        }
+        _ => declared_width.unwrap_or_default() as f64,
+    }
+}
+
+/// Parse the candidates of a srcset, such as `a.jpg 300w, b.jpg 800w`.
+///
+/// Relative urls are resolved against `base`, while candidates which are not http urls are discarded.
+///
+/// Urls are split as described by the HTML spec, so commas inside of urls (e.g. `data:` urls) are preserved.
+pub fn parse_srcset(srcset: &str, base: &Url) -> Vec<SrcsetCandidate> {
+    let mut candidates = Vec::new();
+    let mut input = srcset;
+    loop {
+        input = input.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+        if input.is_empty() {
+            break;
+        }
+        let url_end = input.find(char::is_whitespace).unwrap_or(input.len());
+        let (url, rest) = input.split_at(url_end);
+        let descriptor = if url.ends_with(',') {
+            input = rest;
+            None
+        } else {
+            let descriptor_end = rest.find(',').unwrap_or(rest.len());
+            let (descriptor, rest) = rest.split_at(descriptor_end);
+            input = rest;
+            Some(descriptor.trim())
+                .filter(|descriptor| !descriptor.is_empty())
+                .map(str::to_string)
+        };
+
+        if let Some(url) = parse_http_url(url.trim_end_matches(','), base) {
+            candidates.push(SrcsetCandidate { url, descriptor });
+        }
+    }
+
+    candidates
+}
+
+/// Parse `s` as an url, resolving it against `base` if relative.
+///
+/// Returns `None` if the url is invalid or if its scheme is not http or https (e.g. `data:` placeholders).
+fn parse_http_url(s: &str, base: &Url) -> Option<Url> {
+    let s = s.trim();
+    if s.is_empty() {
+        return None;
+    }
+    base.join(s)
+        .ok()
+        .filter(|url| matches!(url.scheme(), "http" | "https"))
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+    use scraper::{Html, Selector};
+
+    use super::*;
+
+    fn resolve_first_image(html: &str) -> Option<ResolvedSource> {
+        resolve_first_image_with_policy(html, SourcePolicy::Any)
+    }
+
+    fn resolve_first_image_with_policy(html: &str, policy: SourcePolicy) -> Option<ResolvedSource> {
+        let document = Html::parse_fragment(html);
+        let selector = Selector::parse("img").unwrap();
+        resolve(
+            document.select(&selector).next().unwrap(),
+            policy,
+            &page_url(),
+        )
+    }
+
+    fn page_url() -> Url {
+        Url::parse("https://example.com/2024/01/buongiorno/").unwrap()
+    }
+
+    fn resolve_url(html: &str) -> Option<String> {
+        resolve_first_image(html).map(|source| source.url.to_string())
+    }
+
+    #[test]
+    fn should_resolve_src() {
+        assert_eq!(
+            resolve_url(r#"<img src="https://example.com/a.jpg">"#).as_deref(),
+            Some("https://example.com/a.jpg")
+        );
+    }
+
+    #[test]
+    fn should_prefer_lazy_loading_attributes() {
+        assert_eq!(
+            resolve_url(
+                r#"<img src="https://example.com/placeholder.jpg" data-src="https://example.com/a.jpg">"#
+            )
+            .as_deref(),
+            Some("https://example.com/a.jpg")
+        );
+        assert_eq!(
+            resolve_url(
+                r#"<img src="data:image/gif;base64,R0lGOD" data-lazy-src="https://example.com/a.jpg">"#
+            )
+            .as_deref(),
+            Some("https://example.com/a.jpg")
+        );
+        assert_eq!(
+            resolve_url(r#"<img data-original="https://example.com/a.jpg">"#).as_deref(),
+            Some("https://example.com/a.jpg")
+        );
+    }
+
+    #[test]
+    fn should_pick_largest_srcset_candidate() {
+        let source = resolve_first_image(
+            r#"<img src="https://example.com/a-300.jpg" width="300" srcset="https://example.com/a-300.jpg 300w, https://example.com/a-1024.jpg 1024w, https://example.com/a-768.jpg 768w">"#,
+        )
+        .unwrap();
+        assert_eq!(source.url.as_str(), "https://example.com/a-1024.jpg");
+        assert_eq!(source.srcset.len(), 3);
+    }
+
+    #[test]
+    fn should_pick_largest_density_candidate() {
+        assert_eq!(
+            resolve_url(
+                r#"<img src="https://example.com/a.jpg" width="400" data-srcset="https://example.com/a.jpg 1x, https://example.com/a@2x.jpg 2x">"#
+            )
+            .as_deref(),
+            Some("https://example.com/a@2x.jpg")
+        );
+    }
+
+    #[test]
+    fn should_prefer_src_on_tie() {
+        assert_eq!(
+            resolve_url(
+                r#"<img src="https://example.com/a.jpg" width="600" srcset="https://example.com/b.jpg 600w">"#
+            )
+            .as_deref(),
+            Some("https://example.com/a.jpg")
+        );
+    }
+
+    #[test]
+    fn should_resolve_picture_sources() {
+        let source = resolve_first_image(
+            r#"<picture>
+                <source type="image/webp" srcset="https://example.com/a-1200.webp 1200w, https://example.com/a-600.webp 600w">
+                <img src="https://example.com/a-600.jpg" width="600">
+            </picture>"#,
+        )
+        .unwrap();
+        assert_eq!(source.url.as_str(), "https://example.com/a-1200.webp");
+        assert_eq!(source.srcset.len(), 2);
+    }
+
+    #[test]
+    fn should_not_resolve_without_http_sources() {
+        assert!(
+            resolve_first_image(
+                r#"<img src="data:image/gif;base64,R0lGOD" srcset="data:image/gif;base64,R0lGOD 1x">"#
+            )
+            .is_none()
+        );
+    }
+
+    #[test]
+    fn should_resolve_only_lazy_images() {
+        assert!(
+            resolve_first_image_with_policy(
+                r#"<img src="https://example.com/icon.svg">"#,
+                SourcePolicy::LazyOnly
+            )
+            .is_none()
+        );
+        assert_eq!(
+            resolve_first_image_with_policy(
+                r#"<img src="data:image/gif;base64,R0lGOD" data-srcset="https://example.com/a.jpg 800w">"#,
+                SourcePolicy::LazyOnly
+            )
+            .unwrap()
+            .url
+            .as_str(),
+            "https://example.com/a.jpg"
+        );
+    }
+
+    #[test]
+    fn should_parse_srcset() {
+        assert_eq!(
+            parse_srcset(
+                "https://example.com/a.jpg 300w,https://example.com/b.jpg, data:image/gif;base64,R0lGOD 2x",
+                &page_url()
+            ),
+            vec![
+                SrcsetCandidate {
+                    url: Url::parse("https://example.com/a.jpg").unwrap(),
+                    descriptor: Some("300w".to_string()),
+                },
+                SrcsetCandidate {
+                    url: Url::parse("https://example.com/b.jpg").unwrap(),
+                    descriptor: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn should_resolve_relative_sources() {
+        assert_eq!(
+            resolve_url(r#"<img src="/wp-content/uploads/a.jpg">"#).as_deref(),
+            Some("https://example.com/wp-content/uploads/a.jpg")
+        );
+        assert_eq!(
+            resolve_url(r#"<img src="a.jpg">"#).as_deref(),
+            Some("https://example.com/2024/01/buongiorno/a.jpg")
+        );
+        assert_eq!(
+            resolve_url(r#"<img data-src="../../../images/a.jpg">"#).as_deref(),
+            Some("https://example.com/images/a.jpg")
+        );
+    }
+
+    #[test]
+    fn should_resolve_protocol_relative_sources() {
+        assert_eq!(
+            resolve_url(r#"<img src="//cdn.example.com/a.jpg">"#).as_deref(),
+            Some("https://cdn.example.com/a.jpg")
+        );
+    }
+
+    #[test]
+    fn should_resolve_relative_srcset_candidates() {
+        assert_eq!(
+            parse_srcset("/a-300.jpg 300w, //cdn.example.com/a.jpg 800w", &page_url()),
+            vec![
+                SrcsetCandidate {
+                    url: Url::parse("https://example.com/a-300.jpg").unwrap(),
+                    descriptor: Some("300w".to_string()),
+                },
+                SrcsetCandidate {
+                    url: Url::parse("https://cdn.example.com/a.jpg").unwrap(),
+                    descriptor: Some("800w".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn should_not_resolve_non_http_schemes() {
+        assert!(resolve_url(r#"<img src="javascript:void(0)">"#).is_none());
+        assert!(resolve_url(r#"<img src="ftp://example.com/a.jpg">"#).is_none());
+        assert!(resolve_url(r#"<img src="">"#).is_none());
+    }
+}
@@ -6,9 +6,16 @@
 //! ## Features 🎁
 //!
 //! - Different providers to prevent api outages and to differentiate the contents.
+//! - Scrapers to combine providers together: `Fallback` tries providers in order, while `Merged` queries all of them concurrently
+//! - `MemoryCache` to cache the scraped images, coalescing concurrent requests
+//! - `DiskCache` to persist the scraped images across restarts, with stale-while-revalidate
+//! - `Downloader` to download the scraped images, validating their content and size
 //! - Support for different kind of greetings based on the current date
 //! - Utilities functions to retrieve the moveable feasts date (such as Easter, Carnival, Corpus domini...). *requires the `moveable-feasts` feature*
+//! - `ScrapeRandom` to pick random images, with an injectable random number generator
+//! - `Rotation` to deliver the images without repeating them to the same recipient, with in-memory and file-backed history
 //! - A super comfy function `greeting_of_the_day()` to retrieve the best greeting for the day
+//! - `greeting_of_the_moment()` to retrieve the best greeting for the time of the day, such as "buon pranzo" or "buonanotte"
 //!
 //! ## Get started
 //!
@@ -22,16 +29,17 @@
 //!
 //! - `no-log`: disable logging
 //! - `moveable-feasts` (*default*): enable getters for moveable feasts
+//! - `serde`: implement `Serialize` and `Deserialize` for `Greeting` (as its slug), `ScrapeError` and `ScrapedImage`
 //!
 //! ### Scrape for buongiornissimo ☕
 //!
-//! ```rust
-//! use buongiornissimo_rs::{BuongiornissimoCaffe, Scrape};
+//! ```rust,no_run
+//! use buongiornissimo_rs::{BuongiornissimoCaffe, GreetingPolicy, Scrape};
 //! use chrono::Local;
 //!
 //! #[tokio::main]
 //! async fn main() -> anyhow::Result<()> {
-//!     let motd = buongiornissimo_rs::greeting_of_the_day(Local::today().naive_local(), true);
+//!     let motd = buongiornissimo_rs::greeting_of_the_day(Local::now().date_naive(), &GreetingPolicy::default());
 //!     let urls = BuongiornissimoCaffe::default().scrape(motd).await?;
 //!     // Do whatever you want with the scraped images...
 //!     Ok(())
@@ -50,20 +58,53 @@
 #[macro_use]
 extern crate tracing;
 
+use std::sync::Arc;
+
 use async_trait::async_trait;
-use chrono::NaiveDate;
-use thiserror::Error;
+use chrono::{Datelike, NaiveDate, NaiveTime, Timelike};
 use url::Url;
 
 // modules
+mod aggregators;
+mod cache;
+mod download;
+mod error;
+mod fs;
+mod greeting;
+mod http;
+mod image;
+#[cfg(test)]
+mod mock;
 #[cfg(feature = "moveable-feasts")]
 pub mod moveable_feasts;
+mod pagination;
+mod policy;
 mod providers;
+mod random;
+mod rotation;
+#[cfg(test)]
+mod test_server;
+mod time_of_day;
 
 // exports
+pub use aggregators::{Fallback, Merged};
+pub use cache::{DiskCache, MemoryCache};
+pub use download::{DownloadedImage, Downloader, ImageFormat, ImageSource};
+pub use error::{HttpError, ParseGreetingError, ScrapeError, ScrapeResult};
+pub use greeting::GreetingCategory;
+pub use http::{RateLimit, RetryPolicy, RobotsTxt};
+pub use image::{ScrapedImage, SrcsetCandidate};
+pub use pagination::Pagination;
+pub use policy::GreetingPolicy;
 pub use providers::{Augurando, BuongiornissimoCaffe, BuongiornoImmagini, TiCondivido};
+pub use random::ScrapeRandom;
+pub use rotation::{FileHistory, HistoryStore, MemoryHistory, Rotation};
+pub use time_of_day::TimeWindows;
 
-/// Describes the Greeting type
+/// Describes the Greeting type.
+///
+/// Each greeting can be converted from and to a stable kebab-case slug (e.g. `pasqua` or `buon-lunedi`) using [`std::str::FromStr`] and [`std::fmt::Display`],
+/// while [`Greeting::label`] returns its Italian label (e.g. `Buona Pasqua` or `Buon lunedì`).
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub enum Greeting {
     BuonGiorno,
@@ -120,44 +161,59 @@ pub enum Greeting {
     SanSilvestro,
 }
 
-/// Scrape trait result
-pub type ScrapeResult<T> = Result<T, ScrapeError>;
-
-/// Scrape error
-#[derive(Debug, Error, Eq, PartialEq)]
-pub enum ScrapeError {
-    #[error("this scraper doesn't support this greeting type")]
-    UnsupportedGreeting,
-    #[error("http error: {0}")]
-    Http(String),
-    #[error("css parser error: {0}")]
-    Css(String),
-    #[error("unexpected HTML: {0}")]
-    UnexpectedHtml(String),
-    #[error("could not find any image in the page")]
-    NoImages,
-}
+/// The Scrape trait defines the behaviour to scrape the images from the different boomer images providers
+#[async_trait]
+pub trait Scrape: Send + Sync {
+    /// Scrape for a certain kind of greeting.
+    /// Returns the list of the images found, with their metadata
+    async fn scrape_images(&self, greeting: Greeting) -> ScrapeResult<Vec<ScrapedImage>>;
 
-impl From<reqwest::Error> for ScrapeError {
-    fn from(e: reqwest::Error) -> Self {
-        Self::Http(e.to_string())
+    /// Scrape for a certain kind of greeting.
+    /// Returns the list of the image urls
+    async fn scrape(&self, greeting: Greeting) -> ScrapeResult<Vec<Url>> {
+        self.scrape_images(greeting)
+            .await
+            .map(|images| images.into_iter().map(|image| image.url).collect())
+    }
+
+    /// Returns whether this scraper supports the provided greeting, without sending any request
+    fn supports(&self, greeting: Greeting) -> bool;
+
+    /// Returns an iterator over all the greetings supported by this scraper
+    fn supported_greetings(&self) -> Box<dyn Iterator<Item = Greeting> + '_> {
+        Box::new(
+            Greeting::ALL
+                .iter()
+                .copied()
+                .filter(|greeting| self.supports(*greeting)),
+        )
     }
 }
 
-/// The Scrape trait defines the behaviour to scrape the images from the different boomer images providers
+/// A provider shared through an [`Arc`] can be used as a scraper too, so that the same provider can be used by different scrapers
 #[async_trait]
-pub trait Scrape {
-    /// Scrape for a certain kind of greeting.
-    /// Returns the list of the image urls
-    async fn scrape(&self, greeting: Greeting) -> ScrapeResult<Vec<Url>>;
+impl<S> Scrape for Arc<S>
+where
+    S: Scrape + ?Sized,
+{
+    async fn scrape_images(&self, greeting: Greeting) -> ScrapeResult<Vec<ScrapedImage>> {
+        self.as_ref().scrape_images(greeting).await
+    }
+
+    async fn scrape(&self, greeting: Greeting) -> ScrapeResult<Vec<Url>> {
+        self.as_ref().scrape(greeting).await
+    }
+
+    fn supports(&self, greeting: Greeting) -> bool {
+        self.as_ref().supports(greeting)
+    }
 }
 
 /// A utility function to return the greeting for the day based on the current date (considers holiday).
 ///
-/// If `use_weekday` is `true` the greeting returned for regular days will be `BuongiornoWeekday(today.weekday)` otherwise `Buongiorno`
+/// The greeting returned for regular days is selected by the `policy` (e.g. `BuongiornoWeekday(today.weekday)` or `Weekend`).
 /// If the `moveable-feasts` feature is enabled, moveable feasts dates will be considered
-pub fn greeting_of_the_day(date: NaiveDate, use_weekday: bool) -> Greeting {
-    use chrono::Datelike;
+pub fn greeting_of_the_day(date: NaiveDate, policy: &GreetingPolicy) -> Greeting {
     match date {
         date if date.month() == 1 && date.day() == 1 => Greeting::Capodanno,
         date if date.month() == 1 && date.day() == 6 => Greeting::Epifania,
@@ -221,8 +277,30 @@ pub fn greeting_of_the_day(date: NaiveDate, use_weekday: bool) -> Greeting {
         date if date.month() == 12 && date.day() == 25 => Greeting::Natale,
         date if date.month() == 12 && date.day() == 26 => Greeting::SantoStefano,
         date if date.month() == 12 && date.day() == 31 => Greeting::SanSilvestro,
-        date if use_weekday => Greeting::BuonGiornoWeekday(date.weekday()),
-        _ => Greeting::BuonGiorno,
+        date => policy.regular_greeting(date),
+    }
+}
+
+/// A utility function to return the greeting for the moment based on the current date and time.
+///
+/// `datetime` can be either a `NaiveDateTime` or a zoned `DateTime`, in which case its local date and time are considered.
+/// On Friday evening `Weekend` is returned, if enabled by the `policy`.
+/// Otherwise, if the time falls in one of the time windows of the `policy` the time of day greeting is returned (e.g. `BuonPranzo` or `BuonaNotte`),
+/// otherwise it's morning and the greeting is the same as [`greeting_of_the_day`], so feasts take precedence during the morning.
+pub fn greeting_of_the_moment<T>(datetime: &T, policy: &GreetingPolicy) -> Greeting
+where
+    T: Datelike + Timelike,
+{
+    let date = NaiveDate::from_ymd_opt(datetime.year(), datetime.month(), datetime.day())
+        .expect("invalid date");
+    let time = NaiveTime::from_hms_opt(datetime.hour(), datetime.minute(), datetime.second())
+        .expect("invalid time");
+    if policy.is_weekend_evening(date, time) {
+        return Greeting::Weekend;
+    }
+    match policy.time_windows().greeting(time) {
+        Some(greeting) => greeting,
+        None => greeting_of_the_day(date, policy),
     }
 }
 
@@ -254,7 +332,10 @@ mod test {
     #[test]
     fn should_get_greeting_of_the_day_ordinary() {
         assert_eq!(
-            greeting_of_the_day(NaiveDate::from_ymd_opt(2022, 12, 5).unwrap(), false),
+            greeting_of_the_day(
+                NaiveDate::from_ymd_opt(2022, 12, 5).unwrap(),
+                &GreetingPolicy::default().with_weekday(false)
+            ),
             Greeting::BuonGiorno
         );
     }
@@ -262,15 +343,146 @@ mod test {
     #[test]
     fn should_get_greeting_of_the_day_ordinary_weekday() {
         assert_eq!(
-            greeting_of_the_day(NaiveDate::from_ymd_opt(2022, 12, 5).unwrap(), true),
+            greeting_of_the_day(
+                NaiveDate::from_ymd_opt(2022, 12, 5).unwrap(),
+                &GreetingPolicy::default()
+            ),
             Greeting::BuonGiornoWeekday(chrono::Weekday::Mon)
         );
     }
 
+    #[test]
+    fn should_get_greeting_of_the_moment() {
+        let policy = GreetingPolicy::default();
+        let at = |day: u32, hour: u32| {
+            NaiveDate::from_ymd_opt(2022, 12, day)
+                .unwrap()
+                .and_hms_opt(hour, 30, 0)
+                .unwrap()
+        };
+        assert_eq!(
+            greeting_of_the_moment(&at(5, 8), &policy),
+            Greeting::BuonGiornoWeekday(chrono::Weekday::Mon)
+        );
+        assert_eq!(
+            greeting_of_the_moment(&at(5, 8), &policy.with_weekday(false)),
+            Greeting::BuonGiorno
+        );
+        assert_eq!(
+            greeting_of_the_moment(&at(5, 13), &policy),
+            Greeting::BuonPranzo
+        );
+        assert_eq!(
+            greeting_of_the_moment(&at(5, 16), &policy),
+            Greeting::BuonPomeriggio
+        );
+        assert_eq!(
+            greeting_of_the_moment(&at(5, 19), &policy),
+            Greeting::BuonaSerata
+        );
+        assert_eq!(
+            greeting_of_the_moment(&at(5, 21), &policy),
+            Greeting::BuonaCena
+        );
+        assert_eq!(
+            greeting_of_the_moment(&at(5, 23), &policy),
+            Greeting::BuonaNotte
+        );
+        // feasts take precedence only during the morning
+        assert_eq!(
+            greeting_of_the_moment(&at(25, 9), &policy),
+            Greeting::Natale
+        );
+        assert_eq!(
+            greeting_of_the_moment(&at(25, 13), &policy),
+            Greeting::BuonPranzo
+        );
+    }
+
+    #[test]
+    fn should_get_greeting_of_the_moment_with_zoned_time() {
+        use chrono::{FixedOffset, TimeZone};
+
+        let datetime = FixedOffset::east_opt(3600)
+            .unwrap()
+            .with_ymd_and_hms(2022, 12, 25, 10, 0, 0)
+            .unwrap();
+        assert_eq!(
+            greeting_of_the_moment(&datetime, &GreetingPolicy::default()),
+            Greeting::Natale
+        );
+        assert_eq!(
+            greeting_of_the_moment(
+                &datetime,
+                &GreetingPolicy::default().with_time_windows(TimeWindows::default().with_pranzo(
+                    NaiveTime::from_hms_opt(10, 0, 0).unwrap(),
+                    NaiveTime::from_hms_opt(12, 0, 0).unwrap()
+                ))
+            ),
+            Greeting::BuonPranzo
+        );
+    }
+
+    #[test]
+    fn should_get_greeting_of_the_day_weekend() {
+        let policy = GreetingPolicy::default().with_weekend(true);
+        assert_eq!(
+            greeting_of_the_day(NaiveDate::from_ymd_opt(2022, 12, 10).unwrap(), &policy),
+            Greeting::Weekend
+        );
+        assert_eq!(
+            greeting_of_the_day(NaiveDate::from_ymd_opt(2022, 12, 9).unwrap(), &policy),
+            Greeting::BuonGiornoWeekday(chrono::Weekday::Fri)
+        );
+        // feasts take precedence over the weekend
+        assert_eq!(
+            greeting_of_the_day(NaiveDate::from_ymd_opt(2022, 12, 24).unwrap(), &policy),
+            Greeting::VigiliaDiNatale
+        );
+    }
+
+    #[test]
+    fn should_get_greeting_of_the_moment_weekend() {
+        let policy = GreetingPolicy::default().with_weekend(true);
+        let at = |day: u32, hour: u32| {
+            NaiveDate::from_ymd_opt(2022, 12, day)
+                .unwrap()
+                .and_hms_opt(hour, 0, 0)
+                .unwrap()
+        };
+        assert_eq!(
+            greeting_of_the_moment(&at(9, 16), &policy),
+            Greeting::BuonPomeriggio
+        );
+        assert_eq!(
+            greeting_of_the_moment(&at(9, 19), &policy),
+            Greeting::Weekend
+        );
+        assert_eq!(
+            greeting_of_the_moment(&at(9, 23), &policy),
+            Greeting::Weekend
+        );
+        assert_eq!(
+            greeting_of_the_moment(&at(10, 9), &policy),
+            Greeting::Weekend
+        );
+        assert_eq!(
+            greeting_of_the_moment(&at(10, 13), &policy),
+            Greeting::BuonPranzo
+        );
+        assert_eq!(
+            greeting_of_the_moment(&at(9, 19), &GreetingPolicy::default()),
+            Greeting::BuonaSerata
+        );
+    }
+
     #[test]
     fn should_get_greeting_of_the_day_capodanno() {
         assert_eq!(
-            greeting_of_the_day(NaiveDate::from_ymd_opt(2022, 1, 1).unwrap(), true),
+            greeting_of_the_day(
+                NaiveDate::from_ymd_opt(2022, 1, 1).unwrap(),
+                &GreetingPolicy::default()
+            ),
             Greeting::Capodanno
         );
     }
@@ -278,7 +490,10 @@ mod test {
     #[test]
     fn should_get_greeting_of_the_day_epifania() {
         assert_eq!(
-            greeting_of_the_day(NaiveDate::from_ymd_opt(2022, 1, 6).unwrap(), true),
+            greeting_of_the_day(
+                NaiveDate::from_ymd_opt(2022, 1, 6).unwrap(),
+                &GreetingPolicy::default()
+            ),
             Greeting::Epifania
         );
     }
@@ -286,7 +501,10 @@ mod test {
     #[test]
     fn should_get_greeting_of_the_day_san_valentino() {
         assert_eq!(
-            greeting_of_the_day(NaiveDate::from_ymd_opt(2022, 2, 14).unwrap(), true),
+            greeting_of_the_day(
+                NaiveDate::from_ymd_opt(2022, 2, 14).unwrap(),
+                &GreetingPolicy::default()
+            ),
             Greeting::SanValentino
         );
     }
@@ -294,7 +512,10 @@ mod test {
     #[test]
     fn should_get_greeting_of_the_day_festadonne() {
         assert_eq!(
-            greeting_of_the_day(NaiveDate::from_ymd_opt(2022, 3, 8).unwrap(), true),
+            greeting_of_the_day(
+                NaiveDate::from_ymd_opt(2022, 3, 8).unwrap(),
+                &GreetingPolicy::default()
+            ),
             Greeting::FestaDelleDonne
         );
     }
@@ -303,7 +524,10 @@ mod test {
     #[cfg(feature = "moveable-feasts")]
     fn should_get_greeting_of_the_day_giovedi_grasso() {
         assert_eq!(
-            greeting_of_the_day(NaiveDate::from_ymd_opt(2023, 2, 16).unwrap(), true),
+            greeting_of_the_day(
+                NaiveDate::from_ymd_opt(2023, 2, 16).unwrap(),
+                &GreetingPolicy::default()
+            ),
             Greeting::GiovediGrasso
         );
     }
@@ -312,7 +536,10 @@ mod test {
     #[cfg(feature = "moveable-feasts")]
     fn should_get_greeting_of_the_day_martedi_grasso() {
         assert_eq!(
-            greeting_of_the_day(NaiveDate::from_ymd_opt(2023, 2, 21).unwrap(), true),
+            greeting_of_the_day(
+                NaiveDate::from_ymd_opt(2023, 2, 21).unwrap(),
+                &GreetingPolicy::default()
+            ),
             Greeting::MartediGrasso
         );
     }
@@ -321,7 +548,10 @@ mod test {
     #[cfg(feature = "moveable-feasts")]
     fn should_get_greeting_of_the_day_ceneri() {
         assert_eq!(
-            greeting_of_the_day(NaiveDate::from_ymd_opt(2023, 2, 22).unwrap(), true),
+            greeting_of_the_day(
+                NaiveDate::from_ymd_opt(2023, 2, 22).unwrap(),
+                &GreetingPolicy::default()
+            ),
             Greeting::MercolediCeneri
         );
     }
@@ -330,7 +560,10 @@ mod test {
     #[cfg(feature = "moveable-feasts")]
     fn should_get_greeting_of_the_day_palme() {
         assert_eq!(
-            greeting_of_the_day(NaiveDate::from_ymd_opt(2023, 4, 2).unwrap(), true),
+            greeting_of_the_day(
+                NaiveDate::from_ymd_opt(2023, 4, 2).unwrap(),
+                &GreetingPolicy::default()
+            ),
             Greeting::DomenicaDellePalme
         );
     }
@@ -339,7 +572,10 @@ mod test {
     #[cfg(feature = "moveable-feasts")]
     fn should_get_greeting_of_the_day_pasqua() {
         assert_eq!(
-            greeting_of_the_day(NaiveDate::from_ymd_opt(2023, 4, 9).unwrap(), true),
+            greeting_of_the_day(
+                NaiveDate::from_ymd_opt(2023, 4, 9).unwrap(),
+                &GreetingPolicy::default()
+            ),
             Greeting::Pasqua
         );
     }
@@ -348,7 +584,10 @@ mod test {
     #[cfg(feature = "moveable-feasts")]
     fn should_get_greeting_of_the_day_pasquetta() {
         assert_eq!(
-            greeting_of_the_day(NaiveDate::from_ymd_opt(2023, 4, 10).unwrap(), true),
+            greeting_of_the_day(
+                NaiveDate::from_ymd_opt(2023, 4, 10).unwrap(),
+                &GreetingPolicy::default()
+            ),
             Greeting::Pasquetta
         );
     }
@@ -357,7 +596,10 @@ mod test {
     #[cfg(feature = "moveable-feasts")]
     fn should_get_greeting_of_the_day_ascensione() {
         assert_eq!(
-            greeting_of_the_day(NaiveDate::from_ymd_opt(2023, 5, 21).unwrap(), true),
+            greeting_of_the_day(
+                NaiveDate::from_ymd_opt(2023, 5, 21).unwrap(),
+                &GreetingPolicy::default()
+            ),
             Greeting::Ascensione
         );
     }
@@ -366,7 +608,10 @@ mod test {
     #[cfg(feature = "moveable-feasts")]
     fn should_get_greeting_festa_della_mamma() {
         assert_eq!(
-            greeting_of_the_day(NaiveDate::from_ymd_opt(2025, 5, 11).unwrap(), true),
+            greeting_of_the_day(
+                NaiveDate::from_ymd_opt(2025, 5, 11).unwrap(),
+                &GreetingPolicy::default()
+            ),
             Greeting::FestaDellaMamma
         );
     }
@@ -375,7 +620,10 @@ mod test {
     #[cfg(feature = "moveable-feasts")]
     fn should_get_greeting_of_the_day_trinita() {
         assert_eq!(
-            greeting_of_the_day(NaiveDate::from_ymd_opt(2023, 6, 4).unwrap(), true),
+            greeting_of_the_day(
+                NaiveDate::from_ymd_opt(2023, 6, 4).unwrap(),
+                &GreetingPolicy::default()
+            ),
             Greeting::SantissimaTrinita
         );
     }
@@ -384,7 +632,10 @@ mod test {
     #[cfg(feature = "moveable-feasts")]
     fn should_get_greeting_of_the_day_corpus_domini() {
         assert_eq!(
-            greeting_of_the_day(NaiveDate::from_ymd_opt(2023, 6, 11).unwrap(), true),
+            greeting_of_the_day(
+                NaiveDate::from_ymd_opt(2023, 6, 11).unwrap(),
+                &GreetingPolicy::default()
+            ),
             Greeting::CorpusDomini
         );
     }
@@ -393,7 +644,10 @@ mod test {
     #[cfg(feature = "moveable-feasts")]
     fn should_get_greeting_of_the_day_sacro_cuore() {
         assert_eq!(
-            greeting_of_the_day(NaiveDate::from_ymd_opt(2023, 6, 16).unwrap(), true),
+            greeting_of_the_day(
+                NaiveDate::from_ymd_opt(2023, 6, 16).unwrap(),
+                &GreetingPolicy::default()
+            ),
             Greeting::SacroCuoreDiGesu
         );
     }
@@ -402,7 +656,10 @@ mod test {
     #[cfg(feature = "moveable-feasts")]
     fn should_get_greeting_of_the_day_cuore_immacolato() {
         assert_eq!(
-            greeting_of_the_day(NaiveDate::from_ymd_opt(2023, 6, 17).unwrap(), true),
+            greeting_of_the_day(
+                NaiveDate::from_ymd_opt(2023, 6, 17).unwrap(),
+                &GreetingPolicy::default()
+            ),
             Greeting::CuoreImmacolatoDiMaria
         );
     }
@@ -410,7 +667,10 @@ mod test {
     #[test]
     fn should_get_greeting_of_the_day_repubblica() {
         assert_eq!(
-            greeting_of_the_day(NaiveDate::from_ymd_opt(2023, 6, 2).unwrap(), true),
+            greeting_of_the_day(
+                NaiveDate::from_ymd_opt(2023, 6, 2).unwrap(),
+                &GreetingPolicy::default()
+            ),
             Greeting::FestaDellaRepubblica
         );
     }
@@ -418,7 +678,10 @@ mod test {
     #[test]
     fn should_get_greeting_of_the_day_25_aprile() {
         assert_eq!(
-            greeting_of_the_day(NaiveDate::from_ymd_opt(2023, 4, 25).unwrap(), true),
+            greeting_of_the_day(
+                NaiveDate::from_ymd_opt(2023, 4, 25).unwrap(),
+                &GreetingPolicy::default()
+            ),
             Greeting::Liberazione
         );
     }
@@ -426,7 +689,10 @@ mod test {
     #[test]
     fn should_get_greeting_of_the_day_1_maggio() {
         assert_eq!(
-            greeting_of_the_day(NaiveDate::from_ymd_opt(2023, 5, 1).unwrap(), true),
+            greeting_of_the_day(
+                NaiveDate::from_ymd_opt(2023, 5, 1).unwrap(),
+                &GreetingPolicy::default()
+            ),
             Greeting::FestaDeiLavoratori
         );
     }
@@ -434,7 +700,10 @@ mod test {
     #[test]
     fn should_get_greeting_of_the_day_ferragosto() {
         assert_eq!(
-            greeting_of_the_day(NaiveDate::from_ymd_opt(2023, 8, 15).unwrap(), true),
+            greeting_of_the_day(
+                NaiveDate::from_ymd_opt(2023, 8, 15).unwrap(),
+                &GreetingPolicy::default()
+            ),
             Greeting::Ferragosto
         );
     }
@@ -442,7 +711,10 @@ mod test {
     #[test]
     fn should_get_greeting_of_the_day_halloween() {
         assert_eq!(
-            greeting_of_the_day(NaiveDate::from_ymd_opt(2023, 10, 31).unwrap(), true),
+            greeting_of_the_day(
+                NaiveDate::from_ymd_opt(2023, 10, 31).unwrap(),
+                &GreetingPolicy::default()
+            ),
             Greeting::Halloween
         );
     }
@@ -450,7 +722,10 @@ mod test {
     #[test]
     fn should_get_greeting_of_the_day_ognissanti() {
         assert_eq!(
-            greeting_of_the_day(NaiveDate::from_ymd_opt(2023, 11, 1).unwrap(), true),
+            greeting_of_the_day(
+                NaiveDate::from_ymd_opt(2023, 11, 1).unwrap(),
+                &GreetingPolicy::default()
+            ),
             Greeting::Ognissanti
         );
     }
@@ -458,7 +733,10 @@ mod test {
     #[test]
     fn should_get_greeting_of_the_day_defunti() {
         assert_eq!(
-            greeting_of_the_day(NaiveDate::from_ymd_opt(2023, 11, 2).unwrap(), true),
+            greeting_of_the_day(
+                NaiveDate::from_ymd_opt(2023, 11, 2).unwrap(),
+                &GreetingPolicy::default()
+            ),
             Greeting::Defunti
         );
     }
@@ -466,7 +744,10 @@ mod test {
     #[test]
     fn should_get_greeting_of_the_day_sannicola() {
         assert_eq!(
-            greeting_of_the_day(NaiveDate::from_ymd_opt(2023, 12, 6).unwrap(), true),
+            greeting_of_the_day(
+                NaiveDate::from_ymd_opt(2023, 12, 6).unwrap(),
+                &GreetingPolicy::default()
+            ),
             Greeting::SanNicola
         );
     }
@@ -474,7 +755,10 @@ mod test {
     #[test]
     fn should_get_greeting_of_the_day_santambrogio() {
         assert_eq!(
-            greeting_of_the_day(NaiveDate::from_ymd_opt(2023, 12, 7).unwrap(), true),
+            greeting_of_the_day(
+                NaiveDate::from_ymd_opt(2023, 12, 7).unwrap(),
+                &GreetingPolicy::default()
+            ),
             Greeting::SantAmbrogio
         );
     }
@@ -482,7 +766,10 @@ mod test {
     #[test]
     fn should_get_greeting_of_the_day_immacolata() {
         assert_eq!(
-            greeting_of_the_day(NaiveDate::from_ymd_opt(2023, 12, 8).unwrap(), true),
+            greeting_of_the_day(
+                NaiveDate::from_ymd_opt(2023, 12, 8).unwrap(),
+                &GreetingPolicy::default()
+            ),
             Greeting::ImmacolataConcenzione
         );
     }
@@ -490,7 +777,10 @@ mod test {
     #[test]
     fn should_get_greeting_of_the_day_santa_lucia() {
         assert_eq!(
-            greeting_of_the_day(NaiveDate::from_ymd_opt(2023, 12, 13).unwrap(), true),
+            greeting_of_the_day(
+                NaiveDate::from_ymd_opt(2023, 12, 13).unwrap(),
+                &GreetingPolicy::default()
+            ),
             Greeting::SantaLucia
         );
     }
@@ -498,7 +788,10 @@ mod test {
     #[test]
     fn should_get_greeting_of_the_day_silvestro() {
         assert_eq!(
-            greeting_of_the_day(NaiveDate::from_ymd_opt(2023, 12, 31).unwrap(), true),
+            greeting_of_the_day(
+                NaiveDate::from_ymd_opt(2023, 12, 31).unwrap(),
+                &GreetingPolicy::default()
+            ),
             Greeting::SanSilvestro
         );
     }
@@ -506,7 +799,10 @@ mod test {
     #[test]
     fn should_get_greeting_of_the_day_vigilia() {
         assert_eq!(
-            greeting_of_the_day(NaiveDate::from_ymd_opt(2023, 12, 24).unwrap(), true),
+            greeting_of_the_day(
+                NaiveDate::from_ymd_opt(2023, 12, 24).unwrap(),
+                &GreetingPolicy::default()
+            ),
             Greeting::VigiliaDiNatale
         );
     }
@@ -514,7 +810,10 @@ mod test {
     #[test]
     fn should_get_greeting_of_the_day_natale() {
         assert_eq!(
-            greeting_of_the_day(NaiveDate::from_ymd_opt(2023, 12, 25).unwrap(), true),
+            greeting_of_the_day(
+                NaiveDate::from_ymd_opt(2023, 12, 25).unwrap(),
+                &GreetingPolicy::default()
+            ),
             Greeting::Natale
         );
     }
@@ -522,7 +821,10 @@ mod test {
     #[test]
     fn should_get_greeting_of_the_day_santostefano() {
         assert_eq!(
-            greeting_of_the_day(NaiveDate::from_ymd_opt(2023, 12, 26).unwrap(), true),
+            greeting_of_the_day(
+                NaiveDate::from_ymd_opt(2023, 12, 26).unwrap(),
+                &GreetingPolicy::default()
+            ),
             Greeting::SantoStefano
         );
     }
@@ -0,0 +1,70 @@
+//! # Mock
+//!
+//! A mock provider to test the scrapers built on top of other providers
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use url::Url;
+
+use crate::{Greeting, Scrape, ScrapeError, ScrapeResult, ScrapedImage};
+
+/// A provider which returns the configured result for each greeting, and counts the calls to `scrape`.
+///
+/// Greetings without a configured result return [`ScrapeError::UnsupportedGreeting`].
+#[derive(Default)]
+pub struct MockProvider {
+    results: HashMap<Greeting, ScrapeResult<Vec<ScrapedImage>>>,
+    calls: AtomicUsize,
+    delay: Option<Duration>,
+}
+
+impl MockProvider {
+    /// Return the provided urls when scraping `greeting`
+    pub fn with_urls(mut self, greeting: Greeting, urls: &[&str]) -> Self {
+        let page_url = Url::parse("https://mock.it/").unwrap();
+        let images = urls
+            .iter()
+            .map(|url| ScrapedImage::new(Url::parse(url).unwrap(), page_url.clone(), "Mock"))
+            .collect();
+        self.results.insert(greeting, Ok(images));
+        self
+    }
+
+    /// Return the provided error when scraping `greeting`
+    pub fn with_error(mut self, greeting: Greeting, error: ScrapeError) -> Self {
+        self.results.insert(greeting, Err(error));
+        self
+    }
+
+    /// Wait for `delay` before returning the result
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+
+    /// Get the amount of calls to `scrape`
+    pub fn calls(&self) -> usize {
+        self.calls.load(Ordering::SeqCst)
+    }
+}
+
+#[async_trait]
+impl Scrape for MockProvider {
+    async fn scrape_images(&self, greeting: Greeting) -> ScrapeResult<Vec<ScrapedImage>> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        if let Some(delay) = self.delay {
+            tokio::time::sleep(delay).await;
+        }
+        self.results
+            .get(&greeting)
+            .cloned()
+            .unwrap_or(Err(ScrapeError::UnsupportedGreeting))
+    }
+
+    fn supports(&self, greeting: Greeting) -> bool {
+        self.results.contains_key(&greeting)
+    }
+}
@@ -0,0 +1,439 @@
+//! # Pagination
+//!
+//! This module exposes the options to crawl the following pages of a provider listing or article
+
+use std::collections::HashSet;
+
+use scraper::{Html, Selector};
+use url::Url;
+
+use super::http::{HttpClient, Page};
+use super::{ScrapeResult, ScrapedImage};
+
+/// Describes how many pages are crawled by a provider, following the "next page" links.
+///
+/// By default only the first page is scraped.
+/// The following pages are found looking for `rel="next"` links or for links to `/page/N/`,
+/// and they are crawled only if they are "under" the page of the requested [`crate::Greeting`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Pagination {
+    max_pages: usize,
+    max_images: Option<usize>,
+}
+
+impl Default for Pagination {
+    fn default() -> Self {
+        Self {
+            max_pages: 1,
+            max_images: None,
+        }
+    }
+}
+
+impl Pagination {
+    /// Crawl at most `max_pages` pages, including the first one.
+    ///
+    /// A value of `0` is considered as `1`, since the first page is always scraped.
+    pub fn with_max_pages(mut self, max_pages: usize) -> Self {
+        self.max_pages = max_pages.max(1);
+        self
+    }
+
+    /// Stop crawling once `max_images` images have been found; exceeding images are discarded.
+    pub fn with_max_images(mut self, max_images: usize) -> Self {
+        self.max_images = Some(max_images);
+        self
+    }
+
+    /// Get the maximum amount of pages to crawl
+    pub fn max_pages(&self) -> usize {
+        self.max_pages
+    }
+
+    /// Get the maximum amount of images to collect, if any
+    pub fn max_images(&self) -> Option<usize> {
+        self.max_images
+    }
+
+    /// Fetch `url` and the following pages with `client`, parsing each of them with `parse`.
+    ///
+    /// Errors on the first page are returned, while errors on the following pages just stop the crawling.
+    /// Images found on more pages are returned once.
+    pub(crate) async fn crawl<F>(
+        &self,
+        client: &HttpClient,
+        url: Url,
+        parse: F,
+    ) -> ScrapeResult<Vec<ScrapedImage>>
+    where
+        F: Fn(&str, &Url) -> ScrapeResult<Vec<ScrapedImage>>,
+    {
+        let first_page = url.clone();
+        let mut visited = HashSet::new();
+        let mut seen = HashSet::new();
+        let mut images = Vec::new();
+        let mut next = Some(url);
+
+        while let Some(url) = next.take() {
+            let page = client
+                .get_page(&url, |body| {
+                    Ok(Page {
+                        images: parse(body, &url)?,
+                        next: next_page(body, &url),
+                    })
+                })
+                .await;
+            let page = match page {
+                Ok(page) => page,
+                Err(err) if visited.is_empty() => return Err(err),
+                Err(err) => {
+                    error!("failed to scrape page {url}: {err}; stopping crawling");
+                    break;
+                }
+            };
+            visited.insert(url);
+
+            next = page.next.filter(|next| is_under(next, &first_page));
+            images.extend(
+                page.images
+                    .into_iter()
+                    .filter(|image| seen.insert(image.url.clone())),
+            );
+            if let Some(max_images) = self.max_images
+                && images.len() >= max_images
+            {
+                images.truncate(max_images);
+                break;
+            }
+            if visited.len() >= self.max_pages {
+                break;
+            }
+            next = next.filter(|next| !visited.contains(next));
+            if let Some(next) = &next {
+                debug!("crawling next page at {next}");
+            }
+        }
+
+        Ok(images)
+    }
+}
+
+/// Find the url of the page following `page_url` in the provided html.
+///
+/// `rel="next"` links are preferred; otherwise a link to `/page/N/` is searched, where N is the following page number.
+fn next_page(html: &str, page_url: &Url) -> Option<Url> {
+    let document = Html::parse_document(html);
+    let rel_next_selector =
+        Selector::parse(r#"link[rel~="next"][href], a[rel~="next"][href]"#).unwrap();
+    if let Some(url) = document
+        .select(&rel_next_selector)
+        .filter_map(|element| element.value().attr("href"))
+        .find_map(|href| page_url.join(href.trim()).ok())
+    {
+        return Some(url);
+    }
+
+    let expected_path = next_page_path(page_url.path());
+    let link_selector = Selector::parse("a[href]").unwrap();
+    document
+        .select(&link_selector)
+        .filter_map(|element| element.value().attr("href"))
+        .filter_map(|href| page_url.join(href.trim()).ok())
+        .find(|url| {
+            url.host_str() == page_url.host_str()
+                && url.path().trim_end_matches('/') == expected_path
+        })
+}
+
+/// Get the path of the page following `path`, without trailing slash.
+///
+/// `/category/buongiorno/` becomes `/category/buongiorno/page/2`, while `/category/buongiorno/page/2/` becomes `/category/buongiorno/page/3`.
+fn next_page_path(path: &str) -> String {
+    let path = path.trim_end_matches('/');
+    if let Some((base, number)) = path.rsplit_once("/page/")
+        && let Ok(number) = number.parse::<usize>()
+    {
+        return format!("{base}/page/{}", number + 1);
+    }
+    format!("{path}/page/2")
+}
+
+/// Whether `url` belongs to the same site and is located under the path of `first_page`
+fn is_under(url: &Url, first_page: &Url) -> bool {
+    let prefix = first_page.path().trim_end_matches('/');
+    url.scheme() == first_page.scheme()
+        && url.host_str() == first_page.host_str()
+        && url.port_or_known_default() == first_page.port_or_known_default()
+        && url
+            .path()
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::ScrapeError;
+    use crate::test_server::{Response, TestServer};
+
+    fn page(images: &[&str], next: Option<&str>) -> String {
+        let images: String = images
+            .iter()
+            .map(|image| format!(r#"<img src="{image}">"#))
+            .collect();
+        let next = next
+            .map(|next| format!(r#"<link rel="next" href="{next}">"#))
+            .unwrap_or_default();
+        format!("<html><head>{next}</head><body>{images}</body></html>")
+    }
+
+    fn parse(html: &str, page_url: &Url) -> ScrapeResult<Vec<ScrapedImage>> {
+        let document = Html::parse_document(html);
+        let selector = Selector::parse("img").unwrap();
+        let images: Vec<ScrapedImage> = document
+            .select(&selector)
+            .filter_map(|element| element.value().attr("src"))
+            .map(|src| ScrapedImage::new(page_url.join(src).unwrap(), page_url.clone(), "Test"))
+            .collect();
+        if images.is_empty() {
+            return Err(ScrapeError::no_images("Test", page_url));
+        }
+        Ok(images)
+    }
+
+    async fn listing_server() -> TestServer {
+        TestServer::start(|request| match request.path.as_str() {
+            "/buongiorno/" => {
+                Response::ok(page(&["/a.jpg", "/b.jpg"], Some("/buongiorno/page/2/")))
+            }
+            "/buongiorno/page/2/" => {
+                Response::ok(page(&["/b.jpg", "/c.jpg"], Some("/buongiorno/page/3/")))
+            }
+            "/buongiorno/page/3/" => Response::ok(page(&["/d.jpg"], None)),
+            _ => Response::status(404),
+        })
+        .await
+    }
+
+    fn paths(images: &[ScrapedImage]) -> Vec<&str> {
+        images.iter().map(|image| image.url.path()).collect()
+    }
+
+    #[test]
+    fn should_scrape_only_first_page_by_default() {
+        assert_eq!(Pagination::default().max_pages(), 1);
+        assert_eq!(Pagination::default().max_images(), None);
+        assert_eq!(Pagination::default().with_max_pages(0).max_pages(), 1);
+    }
+
+    #[test]
+    fn should_find_rel_next_link() {
+        let page_url = Url::parse("https://example.com/buongiorno/").unwrap();
+        assert_eq!(
+            next_page(
+                r#"<html><head><link rel="next" href="https://example.com/buongiorno/page/2/"></head></html>"#,
+                &page_url
+            )
+            .unwrap()
+            .as_str(),
+            "https://example.com/buongiorno/page/2/"
+        );
+        assert_eq!(
+            next_page(
+                r#"<html><body><a class="next" rel="nofollow next" href="page/2/">Avanti</a></body></html>"#,
+                &page_url
+            )
+            .unwrap()
+            .as_str(),
+            "https://example.com/buongiorno/page/2/"
+        );
+    }
+
+    #[test]
+    fn should_find_page_number_link() {
+        let page_url = Url::parse("https://example.com/buongiorno/page/2/").unwrap();
+        assert_eq!(
+            next_page(
+                r#"<html><body>
+                    <a href="/buongiorno/">1</a>
+                    <a href="/buongiorno/page/2/">2</a>
+                    <a href="/buongiorno/page/3/">3</a>
+                </body></html>"#,
+                &page_url
+            )
+            .unwrap()
+            .as_str(),
+            "https://example.com/buongiorno/page/3/"
+        );
+        assert!(
+            next_page(
+                r#"<html><body><a href="/buongiorno/page/5/">5</a></body></html>"#,
+                &page_url
+            )
+            .is_none()
+        );
+    }
+
+    #[test]
+    fn should_get_next_page_path() {
+        assert_eq!(next_page_path("/buongiorno/"), "/buongiorno/page/2");
+        assert_eq!(next_page_path("/buongiorno/page/2/"), "/buongiorno/page/3");
+        assert_eq!(next_page_path("/"), "/page/2");
+    }
+
+    #[test]
+    fn should_tell_whether_url_is_under_first_page() {
+        let first_page = Url::parse("https://example.com/category/buongiorno/").unwrap();
+        assert!(is_under(
+            &Url::parse("https://example.com/category/buongiorno/page/2/").unwrap(),
+            &first_page
+        ));
+        assert!(!is_under(
+            &Url::parse("https://example.com/category/buonanotte/page/2/").unwrap(),
+            &first_page
+        ));
+        assert!(!is_under(
+            &Url::parse("https://other.com/category/buongiorno/page/2/").unwrap(),
+            &first_page
+        ));
+        assert!(!is_under(
+            &Url::parse("https://example.com/category/buongiorno-speciale/page/2/").unwrap(),
+            &first_page
+        ));
+        assert!(is_under(
+            &Url::parse("https://example.com/category/buongiorno").unwrap(),
+            &first_page
+        ));
+    }
+
+    #[tokio::test]
+    async fn should_crawl_only_first_page() {
+        let server = listing_server().await;
+        let url = server.url().join("/buongiorno/").unwrap();
+        let images = Pagination::default()
+            .crawl(&HttpClient::new("Test"), url, parse)
+            .await
+            .unwrap();
+
+        assert_eq!(paths(&images), vec!["/a.jpg", "/b.jpg"]);
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn should_crawl_next_pages_up_to_max_pages() {
+        let server = listing_server().await;
+        let url = server.url().join("/buongiorno/").unwrap();
+        let images = Pagination::default()
+            .with_max_pages(2)
+            .crawl(&HttpClient::new("Test"), url.clone(), parse)
+            .await
+            .unwrap();
+        assert_eq!(paths(&images), vec!["/a.jpg", "/b.jpg", "/c.jpg"]);
+
+        let images = Pagination::default()
+            .with_max_pages(10)
+            .crawl(&HttpClient::new("Test"), url, parse)
+            .await
+            .unwrap();
+        assert_eq!(paths(&images), vec!["/a.jpg", "/b.jpg", "/c.jpg", "/d.jpg"]);
+        assert_eq!(server.requests().len(), 5);
+    }
+
+    #[tokio::test]
+    async fn should_crawl_up_to_max_images() {
+        let server = listing_server().await;
+        let url = server.url().join("/buongiorno/").unwrap();
+        let images = Pagination::default()
+            .with_max_pages(10)
+            .with_max_images(3)
+            .crawl(&HttpClient::new("Test"), url, parse)
+            .await
+            .unwrap();
+
+        assert_eq!(paths(&images), vec!["/a.jpg", "/b.jpg", "/c.jpg"]);
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn should_stop_crawling_on_failing_page() {
+        let server = TestServer::start(|request| match request.path.as_str() {
+            "/buongiorno/" => Response::ok(page(&["/a.jpg"], Some("/buongiorno/page/2/"))),
+            _ => Response::ok(page(&[], None)),
+        })
+        .await;
+        let url = server.url().join("/buongiorno/").unwrap();
+        let images = Pagination::default()
+            .with_max_pages(10)
+            .crawl(&HttpClient::new("Test"), url, parse)
+            .await
+            .unwrap();
+
+        assert_eq!(paths(&images), vec!["/a.jpg"]);
+    }
+
+    #[tokio::test]
+    async fn should_return_error_on_failing_first_page() {
+        let server = TestServer::serve(&page(&[], Some("/buongiorno/page/2/"))).await;
+        let url = server.url().join("/buongiorno/").unwrap();
+
+        assert_eq!(
+            Pagination::default()
+                .with_max_pages(10)
+                .crawl(&HttpClient::new("Test"), url.clone(), parse)
+                .await
+                .unwrap_err(),
+            ScrapeError::no_images("Test", &url)
+        );
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn should_reuse_pages_not_modified() {
+        let server = TestServer::start(|request| {
+            let etag = format!("\"{}\"", request.path);
+            if request.header("If-None-Match") == Some(etag.as_str()) {
+                return Response::status(304);
+            }
+            let body = match request.path.as_str() {
+                "/buongiorno/" => page(&["/a.jpg"], Some("/buongiorno/page/2/")),
+                _ => page(&["/b.jpg"], None),
+            };
+            Response::ok(body).header("ETag", &etag)
+        })
+        .await;
+        let url = server.url().join("/buongiorno/").unwrap();
+        let client = HttpClient::new("Test");
+        let pagination = Pagination::default().with_max_pages(10);
+
+        let images = pagination.crawl(&client, url.clone(), parse).await.unwrap();
+        assert_eq!(paths(&images), vec!["/a.jpg", "/b.jpg"]);
+        assert_eq!(pagination.crawl(&client, url, parse).await.unwrap(), images);
+        let conditional: Vec<bool> = server
+            .requests()
+            .iter()
+            .map(|request| request.header("If-None-Match").is_some())
+            .collect();
+        assert_eq!(conditional, vec![false, false, true, true]);
+    }
+
+    #[tokio::test]
+    async fn should_not_crawl_pages_of_other_greetings() {
+        let server = TestServer::start(|request| match request.path.as_str() {
+            "/buongiorno/" => Response::ok(page(&["/a.jpg"], Some("/buonanotte/page/2/"))),
+            _ => Response::ok(page(&["/b.jpg"], None)),
+        })
+        .await;
+        let url = server.url().join("/buongiorno/").unwrap();
+        let images = Pagination::default()
+            .with_max_pages(10)
+            .crawl(&HttpClient::new("Test"), url, parse)
+            .await
+            .unwrap();
+
+        assert_eq!(paths(&images), vec!["/a.jpg"]);
+        assert_eq!(server.requests().len(), 1);
+    }
+}
@@ -0,0 +1,132 @@
+//! # Policy
+//!
+//! This module exposes the policy used to select the greeting for a day or a moment
+
+use chrono::{Datelike, NaiveDate, NaiveTime, Weekday};
+
+use super::{Greeting, TimeWindows};
+
+/// The policy used by [`crate::greeting_of_the_day`] and [`crate::greeting_of_the_moment`] to select the greeting
+/// when there's no feast to celebrate.
+///
+/// The default policy:
+///
+/// - returns [`Greeting::BuonGiornoWeekday`] for regular days
+/// - never returns [`Greeting::Weekend`]
+/// - uses the default [`TimeWindows`]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct GreetingPolicy {
+    use_weekday: bool,
+    weekend: bool,
+    weekend_start: NaiveTime,
+    time_windows: TimeWindows,
+}
+
+impl Default for GreetingPolicy {
+    fn default() -> Self {
+        Self {
+            use_weekday: true,
+            weekend: false,
+            weekend_start: NaiveTime::from_hms_opt(18, 0, 0).expect("invalid time"),
+            time_windows: TimeWindows::default(),
+        }
+    }
+}
+
+impl GreetingPolicy {
+    /// If `use_weekday` is `true` the greeting returned for regular days will be `BuonGiornoWeekday(weekday)`, otherwise `BuonGiorno`
+    pub fn with_weekday(mut self, use_weekday: bool) -> Self {
+        self.use_weekday = use_weekday;
+        self
+    }
+
+    /// If `weekend` is `true`, [`Greeting::Weekend`] is returned on Saturday and on Friday evening, instead of the regular greeting.
+    ///
+    /// Friday evening is considered only by [`crate::greeting_of_the_moment`], and it starts at the time set with [`GreetingPolicy::with_weekend_start`]
+    pub fn with_weekend(mut self, weekend: bool) -> Self {
+        self.weekend = weekend;
+        self
+    }
+
+    /// Set the time on Friday from which [`Greeting::Weekend`] is returned, if enabled. Defaults to 18:00
+    pub fn with_weekend_start(mut self, start: NaiveTime) -> Self {
+        self.weekend_start = start;
+        self
+    }
+
+    /// Set the windows in which the time of day greetings are returned by [`crate::greeting_of_the_moment`]
+    pub fn with_time_windows(mut self, time_windows: TimeWindows) -> Self {
+        self.time_windows = time_windows;
+        self
+    }
+
+    /// Get the time windows of the policy
+    pub fn time_windows(&self) -> &TimeWindows {
+        &self.time_windows
+    }
+
+    /// Get the greeting for a regular day
+    pub(crate) fn regular_greeting(&self, date: NaiveDate) -> Greeting {
+        match date.weekday() {
+            Weekday::Sat if self.weekend => Greeting::Weekend,
+            weekday if self.use_weekday => Greeting::BuonGiornoWeekday(weekday),
+            _ => Greeting::BuonGiorno,
+        }
+    }
+
+    /// Get whether it's Friday evening and the weekend greeting should be returned
+    pub(crate) fn is_weekend_evening(&self, date: NaiveDate, time: NaiveTime) -> bool {
+        self.weekend && date.weekday() == Weekday::Fri && time >= self.weekend_start
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn date(day: u32) -> NaiveDate {
+        // 5 december 2022 is a monday
+        NaiveDate::from_ymd_opt(2022, 12, day).unwrap()
+    }
+
+    #[test]
+    fn should_get_regular_greeting() {
+        let policy = GreetingPolicy::default();
+        assert_eq!(
+            policy.regular_greeting(date(10)),
+            Greeting::BuonGiornoWeekday(Weekday::Sat)
+        );
+        assert_eq!(
+            policy.with_weekday(false).regular_greeting(date(10)),
+            Greeting::BuonGiorno
+        );
+        let policy = policy.with_weekend(true);
+        assert_eq!(policy.regular_greeting(date(10)), Greeting::Weekend);
+        assert_eq!(
+            policy.regular_greeting(date(11)),
+            Greeting::BuonGiornoWeekday(Weekday::Sun)
+        );
+        assert_eq!(
+            policy.with_weekday(false).regular_greeting(date(10)),
+            Greeting::Weekend
+        );
+    }
+
+    #[test]
+    fn should_tell_weekend_evening() {
+        let time = |hour| NaiveTime::from_hms_opt(hour, 0, 0).unwrap();
+        let policy = GreetingPolicy::default().with_weekend(true);
+        assert!(policy.is_weekend_evening(date(9), time(18)));
+        assert!(!policy.is_weekend_evening(date(9), time(17)));
+        assert!(!policy.is_weekend_evening(date(8), time(20)));
+        assert!(
+            policy
+                .with_weekend_start(time(16))
+                .is_weekend_evening(date(9), time(17))
+        );
+        assert!(!GreetingPolicy::default().is_weekend_evening(date(9), time(20)));
+    }
+}
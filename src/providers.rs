@@ -2,7 +2,12 @@
 //!
 //! This module exposes all the different providers
 
-use super::{Greeting, Scrape, ScrapeError, ScrapeResult, Url};
+use super::http::HttpClient;
+use super::image::SourcePolicy;
+use super::{
+    Greeting, Pagination, RateLimit, RetryPolicy, RobotsTxt, Scrape, ScrapeError, ScrapeResult,
+    ScrapedImage, Url,
+};
 
 mod augurando;
 mod buongiornissimo_caffe;
@@ -2,75 +2,72 @@
 //!
 //! This provider provides images from <https://augurando.it>
 
-use std::str::FromStr;
-
 use async_trait::async_trait;
 use chrono::Weekday;
-use const_format::concatcp;
+use reqwest::Client;
 use scraper::{Html, Selector};
 
-use super::{Greeting, Scrape, ScrapeError, ScrapeResult, Url};
+use super::{
+    Greeting, HttpClient, Pagination, RateLimit, RetryPolicy, RobotsTxt, Scrape, ScrapeError,
+    ScrapeResult, ScrapedImage, SourcePolicy, Url,
+};
+
+const PROVIDER_NAME: &str = "Augurando";
+const ENTRY_CONTENT_SELECTOR: &str = r#"div[class="entry-content clear"]"#;
 
-const BASE_URL: &str = "https://augurando.it";
-const BUONGIORNO_URL: &str = concatcp!(BASE_URL, "/immagini-buongiorno/");
+const DEFAULT_BASE_URL: &str = "https://augurando.it";
+const BUONGIORNO_PATH: &str = "/immagini-buongiorno/";
 
 // weekdays
-const BUONGIORNO_LUNEDI_URL: &str = concatcp!(BASE_URL, "/immagini-buon-lunedi/");
-const BUONGIORNO_MARTEDI_URL: &str =
-    concatcp!(BASE_URL, "/buon-martedi-con-fantastiche-immagini-entra/");
-const BUONGIORNO_MERCOLEDI_URL: &str = concatcp!(BASE_URL, "/immagini-buon-mercoledi/");
-const BUONGIORNO_GIOVEDI_URL: &str = concatcp!(BASE_URL, "/immagini-buon-giovedi/");
-const BUONGIORNO_VENERDI_URL: &str = concatcp!(BASE_URL, "/immagini-buon-venerdi/");
-const BUONGIORNO_SABATO_URL: &str = concatcp!(BASE_URL, "/immagini-buon-sabato/");
-const BUONGIORNO_DOMENICA_URL: &str = concatcp!(BASE_URL, "/immagini-buona-domenica/");
+const BUONGIORNO_LUNEDI_PATH: &str = "/immagini-buon-lunedi/";
+const BUONGIORNO_MARTEDI_PATH: &str = "/buon-martedi-con-fantastiche-immagini-entra/";
+const BUONGIORNO_MERCOLEDI_PATH: &str = "/immagini-buon-mercoledi/";
+const BUONGIORNO_GIOVEDI_PATH: &str = "/immagini-buon-giovedi/";
+const BUONGIORNO_VENERDI_PATH: &str = "/immagini-buon-venerdi/";
+const BUONGIORNO_SABATO_PATH: &str = "/immagini-buon-sabato/";
+const BUONGIORNO_DOMENICA_PATH: &str = "/immagini-buona-domenica/";
 
-const WEEKEND_URL: &str = concatcp!(BASE_URL, "/immagini-buon-weekend/");
-const BUON_COMPLEANNO_URL: &str = concatcp!(BASE_URL, "/compleanno-immagini-belle/");
+const WEEKEND_PATH: &str = "/immagini-buon-weekend/";
+const BUON_COMPLEANNO_PATH: &str = "/compleanno-immagini-belle/";
 
-const BUON_APPETITO_URL: &str = concatcp!(BASE_URL, "/immagini-buon-appetito/");
+const BUON_APPETITO_PATH: &str = "/immagini-buon-appetito/";
 
-const BUON_POMERIGGIO_URL: &str = concatcp!(BASE_URL, "/immagini-buon-pomeriggio/");
-const BUONA_SERATA_URL: &str = concatcp!(BASE_URL, "/immagini-buona-serata/");
-const BUONA_NOTTE_URL: &str = concatcp!(BASE_URL, "/immagini-buonanotte/");
+const BUON_POMERIGGIO_PATH: &str = "/immagini-buon-pomeriggio/";
+const BUONA_SERATA_PATH: &str = "/immagini-buona-serata/";
+const BUONA_NOTTE_PATH: &str = "/immagini-buonanotte/";
 
 // festivita
-const CAPODANNO_URL: &str = concatcp!(BASE_URL, "/immagini-buon-anno/");
-const BEFANA_URL: &str = concatcp!(BASE_URL, "/immagini-befana/");
-const SAN_VALENTINO_URL: &str = concatcp!(BASE_URL, "/immagini-san-valentino/");
-const CARNEVALE_URL: &str = concatcp!(BASE_URL, "/immagini-buon-carnevale/");
-const FESTA_DELLA_DONNA_URL: &str = concatcp!(BASE_URL, "/immagini-festa-della-donna/");
-const FESTA_DEL_PAPA_URL: &str = concatcp!(BASE_URL, "/immagini-festa-del-papa/");
-const PALME_URL: &str = concatcp!(BASE_URL, "/immagini-buona-domenica-delle-palme/");
-const PASQUA_URL: &str = concatcp!(BASE_URL, "/immagini-buona-pasqua/");
-const PASQUETTA_URL: &str = concatcp!(BASE_URL, "/immagini-buona-pasquetta/");
-const URL_25_APRILE_URL: &str = concatcp!(BASE_URL, "/25-aprile/");
-const PRIMO_MAGGIO_URL: &str = concatcp!(BASE_URL, "/immagini-1-maggio/");
-const FESTA_DELLA_MAMMA_URL: &str = concatcp!(BASE_URL, "/immagini-festa-della-mamma/");
-const DUE_GIUGNO_URL: &str = concatcp!(BASE_URL, "/immagini-2-giugno/");
-const FERRAGOSTO_URL: &str = concatcp!(BASE_URL, "/immagini-buon-ferragosto/");
-const HALLOWEEEN_URL: &str = concatcp!(BASE_URL, "/immagini-halloween/");
-const OGNISSANTI_URL: &str = concatcp!(BASE_URL, "/1-novembre/");
-const DEFUNTI_URL: &str = concatcp!(BASE_URL, "/2-novembre/");
-const IMMACOLATA_URL: &str = concatcp!(BASE_URL, "/immagini-immacolata-concezione/");
-const SAN_NICOLA_URL: &str = concatcp!(BASE_URL, "/immagini-san-nicola/");
-const SANTAMBROGIO_URL: &str = concatcp!(BASE_URL, "/immagini-sant-ambrogio/");
-const BUON_NATALE_URL: &str = concatcp!(BASE_URL, "/immagini-buon-natale/");
-const SANTO_STEFANO_URL: &str = concatcp!(BASE_URL, "/santo-stefano-26-dicembre/");
-const SAN_SILVESTRO_URL: &str = concatcp!(BASE_URL, "/immagini-vigilia-di-capodanno/");
-const SANTA_LUCIA_URL: &str = concatcp!(BASE_URL, "/immagini-santa-lucia/");
+const CAPODANNO_PATH: &str = "/immagini-buon-anno/";
+const BEFANA_PATH: &str = "/immagini-befana/";
+const SAN_VALENTINO_PATH: &str = "/immagini-san-valentino/";
+const CARNEVALE_PATH: &str = "/immagini-buon-carnevale/";
+const FESTA_DELLA_DONNA_PATH: &str = "/immagini-festa-della-donna/";
+const FESTA_DEL_PAPA_PATH: &str = "/immagini-festa-del-papa/";
+const PALME_PATH: &str = "/immagini-buona-domenica-delle-palme/";
+const PASQUA_PATH: &str = "/immagini-buona-pasqua/";
+const PASQUETTA_PATH: &str = "/immagini-buona-pasquetta/";
+const URL_25_APRILE_PATH: &str = "/25-aprile/";
+const PRIMO_MAGGIO_PATH: &str = "/immagini-1-maggio/";
+const FESTA_DELLA_MAMMA_PATH: &str = "/immagini-festa-della-mamma/";
+const DUE_GIUGNO_PATH: &str = "/immagini-2-giugno/";
+const FERRAGOSTO_PATH: &str = "/immagini-buon-ferragosto/";
+const HALLOWEEEN_PATH: &str = "/immagini-halloween/";
+const OGNISSANTI_PATH: &str = "/1-novembre/";
+const DEFUNTI_PATH: &str = "/2-novembre/";
+const IMMACOLATA_PATH: &str = "/immagini-immacolata-concezione/";
+const SAN_NICOLA_PATH: &str = "/immagini-san-nicola/";
+const SANTAMBROGIO_PATH: &str = "/immagini-sant-ambrogio/";
+const BUON_NATALE_PATH: &str = "/immagini-buon-natale/";
+const SANTO_STEFANO_PATH: &str = "/santo-stefano-26-dicembre/";
+const SAN_SILVESTRO_PATH: &str = "/immagini-vigilia-di-capodanno/";
+const SANTA_LUCIA_PATH: &str = "/immagini-santa-lucia/";
 
 /// Buongiornissimo provider which scrapes images from <https://augurando.it>
 ///
-/// Supported [`Greeting`]s:
+/// Supported [`Greeting`]s (see also [`Scrape::supports`]):
 ///
 /// - [`Greeting::BuonGiorno`]
-/// - [`Greeting::BuonGiornoWeekday`]
-/// - [`Greeting::BuonGiornoWeekday`]
-/// - [`Greeting::BuonGiornoWeekday`]
-/// - [`Greeting::BuonGiornoWeekday`]
-/// - [`Greeting::BuonGiornoWeekday`]
-/// - [`Greeting::BuonGiornoWeekday`]
-/// - [`Greeting::BuonGiornoWeekday`]
+/// - [`Greeting::BuonGiornoWeekday`] (every weekday)
 /// - [`Greeting::Weekend`]
 /// - [`Greeting::BuonPranzo`]
 /// - [`Greeting::BuonaCena`]
@@ -103,98 +100,182 @@ const SANTA_LUCIA_URL: &str = concatcp!(BASE_URL, "/immagini-santa-lucia/");
 /// - [`Greeting::SantoStefano`]
 /// - [`Greeting::SanSilvestro`]
 /// - [`Greeting::SantaLucia`]
-#[derive(Default)]
-pub struct Augurando;
+#[derive(Debug, Clone)]
+pub struct Augurando {
+    http: HttpClient,
+    base_url: Url,
+    pagination: Pagination,
+}
+
+impl Default for Augurando {
+    fn default() -> Self {
+        Self {
+            http: HttpClient::new(PROVIDER_NAME),
+            base_url: Url::parse(DEFAULT_BASE_URL).expect("invalid default base url"),
+            pagination: Pagination::default(),
+        }
+    }
+}
 
 impl Augurando {
-    fn get_url(greeting: Greeting) -> ScrapeResult<String> {
+    /// Use the provided [`Client`] to send the requests, instead of a default one.
+    ///
+    /// This allows to share a single tuned client (connection pool, timeouts, proxy, user-agent...) between providers.
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.http.set_client(client);
+        self
+    }
+
+    /// Retry the requests which fail for a transient error, as described by `retry`.
+    ///
+    /// By default requests are not retried.
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.http.set_retry(retry);
+        self
+    }
+
+    /// Limit the rate of the requests sent to the provider host, as described by `rate_limit`.
+    ///
+    /// The limit is shared with all the other providers sending requests to the same host.
+    pub fn with_rate_limit(mut self, rate_limit: RateLimit) -> Self {
+        self.http.set_rate_limit(rate_limit);
+        self
+    }
+
+    /// Comply with the `robots.txt` of the provider site, as described by `robots`.
+    ///
+    /// Disallowed pages are not requested and [`ScrapeError::RobotsDisallowed`] is returned instead.
+    pub fn with_robots_txt(mut self, robots: RobotsTxt) -> Self {
+        self.http.set_robots_txt(robots);
+        self
+    }
+
+    /// Use the provided base url to build the pages urls, instead of <https://augurando.it>.
+    ///
+    /// The per-greeting paths are appended to the base url, so this can be used to point the provider to a mirror or to a local server.
+    pub fn with_base_url(mut self, base_url: Url) -> Self {
+        self.base_url = base_url;
+        self
+    }
+
+    /// Crawl the following pages of the article, as described by `pagination`, instead of the first one only.
+    pub fn with_pagination(mut self, pagination: Pagination) -> Self {
+        self.pagination = pagination;
+        self
+    }
+
+    fn get_url(&self, greeting: Greeting) -> ScrapeResult<Url> {
+        Self::get_path(greeting).map(|path| {
+            let url = format!("{}{}", self.base_url.as_str().trim_end_matches('/'), path);
+            Url::parse(&url).expect("invalid page url")
+        })
+    }
+
+    fn get_path(greeting: Greeting) -> ScrapeResult<String> {
         match greeting {
-            Greeting::BuonGiorno => Ok(BUONGIORNO_URL.to_string()),
-            Greeting::BuonGiornoWeekday(Weekday::Sun) => Ok(BUONGIORNO_DOMENICA_URL.to_string()),
-            Greeting::BuonGiornoWeekday(Weekday::Mon) => Ok(BUONGIORNO_LUNEDI_URL.to_string()),
-            Greeting::BuonGiornoWeekday(Weekday::Tue) => Ok(BUONGIORNO_MARTEDI_URL.to_string()),
-            Greeting::BuonGiornoWeekday(Weekday::Wed) => Ok(BUONGIORNO_MERCOLEDI_URL.to_string()),
-            Greeting::BuonGiornoWeekday(Weekday::Thu) => Ok(BUONGIORNO_GIOVEDI_URL.to_string()),
-            Greeting::BuonGiornoWeekday(Weekday::Fri) => Ok(BUONGIORNO_VENERDI_URL.to_string()),
-            Greeting::BuonGiornoWeekday(Weekday::Sat) => Ok(BUONGIORNO_SABATO_URL.to_string()),
-            Greeting::Weekend => Ok(WEEKEND_URL.to_string()),
-            Greeting::BuonPranzo | Greeting::BuonaCena => Ok(BUON_APPETITO_URL.to_string()),
-            Greeting::BuonPomeriggio => Ok(BUON_POMERIGGIO_URL.to_string()),
-            Greeting::BuonaNotte => Ok(BUONA_NOTTE_URL.to_string()),
-            Greeting::BuonaSerata => Ok(BUONA_SERATA_URL.to_string()),
-            Greeting::Compleanno => Ok(BUON_COMPLEANNO_URL.to_string()),
-            Greeting::Capodanno => Ok(CAPODANNO_URL.to_string()),
-            Greeting::Epifania => Ok(BEFANA_URL.to_string()),
-            Greeting::SanValentino => Ok(SAN_VALENTINO_URL.to_string()),
-            Greeting::GiovediGrasso | Greeting::MartediGrasso => Ok(CARNEVALE_URL.to_string()),
-            Greeting::FestaDelleDonne => Ok(FESTA_DELLA_DONNA_URL.to_string()),
-            Greeting::FestaDelPapa => Ok(FESTA_DEL_PAPA_URL.to_string()),
-            Greeting::DomenicaDellePalme => Ok(PALME_URL.to_string()),
-            Greeting::Pasqua => Ok(PASQUA_URL.to_string()),
-            Greeting::Pasquetta => Ok(PASQUETTA_URL.to_string()),
-            Greeting::Liberazione => Ok(URL_25_APRILE_URL.to_string()),
-            Greeting::FestaDeiLavoratori => Ok(PRIMO_MAGGIO_URL.to_string()),
-            Greeting::FestaDellaMamma => Ok(FESTA_DELLA_MAMMA_URL.to_string()),
-            Greeting::DueGiugno => Ok(DUE_GIUGNO_URL.to_string()),
-            Greeting::Ferragosto => Ok(FERRAGOSTO_URL.to_string()),
-            Greeting::Halloween => Ok(HALLOWEEEN_URL.to_string()),
-            Greeting::Ognissanti => Ok(OGNISSANTI_URL.to_string()),
-            Greeting::Defunti => Ok(DEFUNTI_URL.to_string()),
-            Greeting::ImmacolataConcenzione => Ok(IMMACOLATA_URL.to_string()),
-            Greeting::SanNicola => Ok(SAN_NICOLA_URL.to_string()),
-            Greeting::SantAmbrogio => Ok(SANTAMBROGIO_URL.to_string()),
-            Greeting::Natale => Ok(BUON_NATALE_URL.to_string()),
-            Greeting::SantoStefano => Ok(SANTO_STEFANO_URL.to_string()),
-            Greeting::SanSilvestro => Ok(SAN_SILVESTRO_URL.to_string()),
-            Greeting::SantaLucia => Ok(SANTA_LUCIA_URL.to_string()),
+            Greeting::BuonGiorno => Ok(BUONGIORNO_PATH.to_string()),
+            Greeting::BuonGiornoWeekday(Weekday::Sun) => Ok(BUONGIORNO_DOMENICA_PATH.to_string()),
+            Greeting::BuonGiornoWeekday(Weekday::Mon) => Ok(BUONGIORNO_LUNEDI_PATH.to_string()),
+            Greeting::BuonGiornoWeekday(Weekday::Tue) => Ok(BUONGIORNO_MARTEDI_PATH.to_string()),
+            Greeting::BuonGiornoWeekday(Weekday::Wed) => Ok(BUONGIORNO_MERCOLEDI_PATH.to_string()),
+            Greeting::BuonGiornoWeekday(Weekday::Thu) => Ok(BUONGIORNO_GIOVEDI_PATH.to_string()),
+            Greeting::BuonGiornoWeekday(Weekday::Fri) => Ok(BUONGIORNO_VENERDI_PATH.to_string()),
+            Greeting::BuonGiornoWeekday(Weekday::Sat) => Ok(BUONGIORNO_SABATO_PATH.to_string()),
+            Greeting::Weekend => Ok(WEEKEND_PATH.to_string()),
+            Greeting::BuonPranzo | Greeting::BuonaCena => Ok(BUON_APPETITO_PATH.to_string()),
+            Greeting::BuonPomeriggio => Ok(BUON_POMERIGGIO_PATH.to_string()),
+            Greeting::BuonaNotte => Ok(BUONA_NOTTE_PATH.to_string()),
+            Greeting::BuonaSerata => Ok(BUONA_SERATA_PATH.to_string()),
+            Greeting::Compleanno => Ok(BUON_COMPLEANNO_PATH.to_string()),
+            Greeting::Capodanno => Ok(CAPODANNO_PATH.to_string()),
+            Greeting::Epifania => Ok(BEFANA_PATH.to_string()),
+            Greeting::SanValentino => Ok(SAN_VALENTINO_PATH.to_string()),
+            Greeting::GiovediGrasso | Greeting::MartediGrasso => Ok(CARNEVALE_PATH.to_string()),
+            Greeting::FestaDelleDonne => Ok(FESTA_DELLA_DONNA_PATH.to_string()),
+            Greeting::FestaDelPapa => Ok(FESTA_DEL_PAPA_PATH.to_string()),
+            Greeting::DomenicaDellePalme => Ok(PALME_PATH.to_string()),
+            Greeting::Pasqua => Ok(PASQUA_PATH.to_string()),
+            Greeting::Pasquetta => Ok(PASQUETTA_PATH.to_string()),
+            Greeting::Liberazione => Ok(URL_25_APRILE_PATH.to_string()),
+            Greeting::FestaDeiLavoratori => Ok(PRIMO_MAGGIO_PATH.to_string()),
+            Greeting::FestaDellaMamma => Ok(FESTA_DELLA_MAMMA_PATH.to_string()),
+            Greeting::DueGiugno => Ok(DUE_GIUGNO_PATH.to_string()),
+            Greeting::Ferragosto => Ok(FERRAGOSTO_PATH.to_string()),
+            Greeting::Halloween => Ok(HALLOWEEEN_PATH.to_string()),
+            Greeting::Ognissanti => Ok(OGNISSANTI_PATH.to_string()),
+            Greeting::Defunti => Ok(DEFUNTI_PATH.to_string()),
+            Greeting::ImmacolataConcenzione => Ok(IMMACOLATA_PATH.to_string()),
+            Greeting::SanNicola => Ok(SAN_NICOLA_PATH.to_string()),
+            Greeting::SantAmbrogio => Ok(SANTAMBROGIO_PATH.to_string()),
+            Greeting::Natale => Ok(BUON_NATALE_PATH.to_string()),
+            Greeting::SantoStefano => Ok(SANTO_STEFANO_PATH.to_string()),
+            Greeting::SanSilvestro => Ok(SAN_SILVESTRO_PATH.to_string()),
+            Greeting::SantaLucia => Ok(SANTA_LUCIA_PATH.to_string()),
             _ => Err(ScrapeError::UnsupportedGreeting),
         }
     }
-}
 
-#[async_trait]
-impl Scrape for Augurando {
-    async fn scrape(&self, greeting: Greeting) -> ScrapeResult<Vec<Url>> {
-        let url = Self::get_url(greeting)?;
-        debug!("scraping greeting of kind {:?} at {}", greeting, url);
-        // send request
-        let body = reqwest::get(&url).await?.text().await?;
-        debug!("got body of length {}", body.len());
+    /// Parse the html page of the provider, located at `page_url`, and return the urls of the images found in it.
+    ///
+    /// This can be used to scrape pages which have been fetched by other means.
+    pub fn parse_html(html: &str, page_url: &Url) -> ScrapeResult<Vec<Url>> {
+        Self::parse_images(html, page_url)
+            .map(|images| images.into_iter().map(|image| image.url).collect())
+    }
+
+    /// Parse the html page of the provider, located at `page_url`, and return the images found in it.
+    ///
+    /// This is what [`Scrape::scrape_images`] runs on the fetched page.
+    pub fn parse_images(html: &str, page_url: &Url) -> ScrapeResult<Vec<ScrapedImage>> {
         // parse document
-        let document = Html::parse_document(&body);
+        let document = Html::parse_document(html);
         debug!("html document parsed");
         // search for entry content selector
-        let main_selector = Selector::parse(r#"div[class="entry-content clear"]"#).unwrap();
+        let main_selector = Selector::parse(ENTRY_CONTENT_SELECTOR).unwrap();
         let mut main_elements = document.select(&main_selector);
         let main = main_elements.next();
         let Some(main) = main else {
             error!("main is none");
-            return Err(ScrapeError::UnexpectedHtml(
-                "could not find a div with class entry-content".to_string(),
-            ));
+            return Err(ScrapeError::UnexpectedHtml {
+                provider: PROVIDER_NAME.to_string(),
+                url: Box::new(page_url.clone()),
+                selector: ENTRY_CONTENT_SELECTOR.to_string(),
+            });
         };
 
         debug!("selecting images in thumbnail");
-        let mut urls = Vec::new();
+        let mut images = Vec::new();
         let img_selector = Selector::parse("img").unwrap();
-        let images = main.select(&img_selector);
-        for image in images {
-            if let Some(Ok(url)) = image
-                .value()
-                .attr("src")
-                .filter(|s| s.starts_with("http") || s.starts_with("https"))
-                .map(Url::from_str)
+        let elements = main.select(&img_selector);
+        for element in elements {
+            if let Some(image) =
+                ScrapedImage::from_element(element, SourcePolicy::Any, page_url, PROVIDER_NAME)
             {
-                debug!("found image with url {}", url);
-                urls.push(url)
+                debug!("found image with url {}", image.url);
+                images.push(image)
             }
         }
 
-        if urls.is_empty() {
-            error!("urls is empty");
-            return Err(ScrapeError::NoImages);
+        if images.is_empty() {
+            error!("images is empty");
+            return Err(ScrapeError::no_images(PROVIDER_NAME, page_url));
         }
-        Ok(urls)
+        Ok(images)
+    }
+}
+
+#[async_trait]
+impl Scrape for Augurando {
+    async fn scrape_images(&self, greeting: Greeting) -> ScrapeResult<Vec<ScrapedImage>> {
+        let url = self.get_url(greeting)?;
+        debug!("scraping greeting of kind {:?} at {}", greeting, url);
+        self.pagination
+            .crawl(&self.http, url, Self::parse_images)
+            .await
+    }
+
+    fn supports(&self, greeting: Greeting) -> bool {
+        Self::get_path(greeting).is_ok()
     }
 }
 
@@ -202,8 +283,179 @@ impl Scrape for Augurando {
 mod test {
 
     use super::*;
+    use crate::test_server::TestServer;
+
+    const IMAGES_FIXTURE: &str = include_str!("../../tests/fixtures/augurando/article.html");
+    const NO_IMAGES_FIXTURE: &str = include_str!("../../tests/fixtures/augurando/no_images.html");
+    const NO_CONTENT_FIXTURE: &str = include_str!("../../tests/fixtures/no_content.html");
+
+    fn page_url() -> Url {
+        Url::parse("https://augurando.it/immagini-buongiorno/").unwrap()
+    }
+
+    #[test]
+    fn should_get_url_with_default_base_url() {
+        assert_eq!(
+            Augurando::default()
+                .get_url(Greeting::BuonGiorno)
+                .unwrap()
+                .as_str(),
+            "https://augurando.it/immagini-buongiorno/"
+        );
+    }
+
+    #[test]
+    fn should_get_url_with_custom_base_url() {
+        let provider = Augurando::default()
+            .with_base_url(Url::parse("http://localhost:8080/mirror/").unwrap());
+        assert_eq!(
+            provider.get_url(Greeting::BuonGiorno).unwrap().as_str(),
+            "http://localhost:8080/mirror/immagini-buongiorno/"
+        );
+    }
+
+    #[test]
+    fn should_tell_supported_greetings() {
+        let provider = Augurando::default();
+        assert_eq!(
+            provider.supported_greetings().collect::<Vec<_>>(),
+            vec![
+                Greeting::BuonGiorno,
+                Greeting::BuonGiornoWeekday(Weekday::Mon),
+                Greeting::BuonGiornoWeekday(Weekday::Tue),
+                Greeting::BuonGiornoWeekday(Weekday::Wed),
+                Greeting::BuonGiornoWeekday(Weekday::Thu),
+                Greeting::BuonGiornoWeekday(Weekday::Fri),
+                Greeting::BuonGiornoWeekday(Weekday::Sat),
+                Greeting::BuonGiornoWeekday(Weekday::Sun),
+                Greeting::Weekend,
+                Greeting::BuonPomeriggio,
+                Greeting::BuonPranzo,
+                Greeting::BuonaNotte,
+                Greeting::BuonaSerata,
+                Greeting::BuonaCena,
+                Greeting::Compleanno,
+                Greeting::Capodanno,
+                Greeting::Epifania,
+                Greeting::SanValentino,
+                Greeting::GiovediGrasso,
+                Greeting::MartediGrasso,
+                Greeting::FestaDelleDonne,
+                Greeting::FestaDelPapa,
+                Greeting::FestaDellaMamma,
+                Greeting::DomenicaDellePalme,
+                Greeting::Pasqua,
+                Greeting::Pasquetta,
+                Greeting::Liberazione,
+                Greeting::FestaDeiLavoratori,
+                Greeting::DueGiugno,
+                Greeting::Ferragosto,
+                Greeting::Halloween,
+                Greeting::Ognissanti,
+                Greeting::Defunti,
+                Greeting::SanNicola,
+                Greeting::SantAmbrogio,
+                Greeting::ImmacolataConcenzione,
+                Greeting::SantaLucia,
+                Greeting::Natale,
+                Greeting::SantoStefano,
+                Greeting::SanSilvestro,
+            ]
+        );
+    }
+
+    #[test]
+    fn should_parse_images_from_html() {
+        assert_eq!(
+            Augurando::parse_html(IMAGES_FIXTURE, &page_url()).unwrap(),
+            vec![
+                Url::parse(
+                    "https://augurando.it/wp-content/uploads/2024/03/buongiorno-caffe-fumante.jpg"
+                )
+                .unwrap(),
+                Url::parse(
+                    "https://augurando.it/wp-content/uploads/2024/03/buongiorno-fiori-di-campo.jpg"
+                )
+                .unwrap(),
+                Url::parse(
+                    "https://augurando.it/wp-content/uploads/2024/03/buongiorno-gattino.webp"
+                )
+                .unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_parse_images_metadata_from_html() {
+        let images = Augurando::parse_images(IMAGES_FIXTURE, &page_url()).unwrap();
+        assert_eq!(
+            images[0],
+            ScrapedImage {
+                url: Url::parse(
+                    "https://augurando.it/wp-content/uploads/2024/03/buongiorno-caffe-fumante.jpg"
+                )
+                .unwrap(),
+                alt: Some("Buongiorno caffè fumante".to_string()),
+                width: Some(1024),
+                height: Some(1024),
+                srcset: vec![],
+                page_url: page_url(),
+                provider: "Augurando".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn should_not_parse_html_without_entry_content() {
+        assert!(matches!(
+            Augurando::parse_html(NO_CONTENT_FIXTURE, &page_url()),
+            Err(ScrapeError::UnexpectedHtml { .. })
+        ));
+    }
+
+    #[test]
+    fn should_not_parse_html_without_images() {
+        assert_eq!(
+            Augurando::parse_html(NO_IMAGES_FIXTURE, &page_url()).unwrap_err(),
+            ScrapeError::no_images(PROVIDER_NAME, &page_url())
+        );
+    }
+
+    #[tokio::test]
+    async fn should_scrape_images_from_base_url() {
+        let server = TestServer::serve(IMAGES_FIXTURE).await;
+        let urls = Augurando::default()
+            .with_base_url(server.url())
+            .scrape(Greeting::BuonGiorno)
+            .await
+            .unwrap();
+        assert_eq!(urls.len(), 3);
+        assert_eq!(server.requests()[0].path, "/immagini-buongiorno/");
+    }
+
+    #[tokio::test]
+    async fn should_return_http_error_if_site_is_unreachable() {
+        let provider =
+            Augurando::default().with_base_url(Url::parse("http://127.0.0.1:1/").unwrap());
+        assert!(matches!(
+            provider.scrape(Greeting::BuonGiorno).await,
+            Err(ScrapeError::Connect { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn should_not_scrape_unsupported_greeting() {
+        assert_eq!(
+            Augurando::default()
+                .scrape(Greeting::SantissimaTrinita)
+                .await
+                .unwrap_err(),
+            ScrapeError::UnsupportedGreeting
+        );
+    }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn test_buongiorno() {
         let provider = Augurando::default();
         let urls = provider.scrape(Greeting::BuonGiorno).await.unwrap();
@@ -211,6 +463,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn test_buon_pranzo() {
         let provider = Augurando::default();
         let urls = provider.scrape(Greeting::BuonPranzo).await.unwrap();
@@ -218,6 +471,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn test_buongiorno_weekday() {
         let provider = Augurando::default();
         let urls = provider
@@ -228,6 +482,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn test_weekend() {
         let provider = Augurando::default();
         let urls = provider.scrape(Greeting::Weekend).await.unwrap();
@@ -235,6 +490,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn test_buona_notte() {
         let provider = Augurando::default();
         let urls = provider.scrape(Greeting::BuonaNotte).await.unwrap();
@@ -242,6 +498,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn test_buon_pomeriggio() {
         let provider = Augurando::default();
         let urls = provider.scrape(Greeting::BuonPomeriggio).await.unwrap();
@@ -249,6 +506,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn test_buona_serata() {
         let provider = Augurando::default();
         let urls = provider.scrape(Greeting::BuonaSerata).await.unwrap();
@@ -256,6 +514,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn test_compleanno() {
         let provider = Augurando::default();
         let urls = provider.scrape(Greeting::Compleanno).await.unwrap();
@@ -263,6 +522,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn test_capodanno() {
         let provider = Augurando::default();
         let urls = provider.scrape(Greeting::Capodanno).await.unwrap();
@@ -270,6 +530,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn test_epifania() {
         let provider = Augurando::default();
         let urls = provider.scrape(Greeting::Epifania).await.unwrap();
@@ -277,6 +538,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn test_san_valentino() {
         let provider = Augurando::default();
         let urls = provider.scrape(Greeting::SanValentino).await.unwrap();
@@ -284,6 +546,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn test_carnevale() {
         let provider = Augurando::default();
         let urls = provider.scrape(Greeting::GiovediGrasso).await.unwrap();
@@ -291,6 +554,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn test_festa_delle_donne() {
         let provider = Augurando::default();
         let urls = provider.scrape(Greeting::FestaDelleDonne).await.unwrap();
@@ -298,6 +562,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn test_festa_del_papa() {
         let provider = Augurando::default();
         let urls = provider.scrape(Greeting::FestaDelPapa).await.unwrap();
@@ -305,6 +570,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn test_domenica_delle_palme() {
         let provider = Augurando::default();
         let urls = provider.scrape(Greeting::DomenicaDellePalme).await.unwrap();
@@ -312,6 +578,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn test_pasqua() {
         let provider = Augurando::default();
         let urls = provider.scrape(Greeting::Pasqua).await.unwrap();
@@ -319,6 +586,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn test_pasquetta() {
         let provider = Augurando::default();
         let urls = provider.scrape(Greeting::Pasquetta).await.unwrap();
@@ -326,6 +594,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn test_liberazione() {
         let provider = Augurando::default();
         let urls = provider.scrape(Greeting::Liberazione).await.unwrap();
@@ -333,6 +602,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn test_festa_dei_lavoratori() {
         let provider = Augurando::default();
         let urls = provider.scrape(Greeting::FestaDeiLavoratori).await.unwrap();
@@ -340,6 +610,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn test_festa_della_mamma() {
         let provider = Augurando::default();
         let urls = provider.scrape(Greeting::FestaDellaMamma).await.unwrap();
@@ -347,6 +618,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn test_due_giugno() {
         let provider = Augurando::default();
         let urls = provider.scrape(Greeting::DueGiugno).await.unwrap();
@@ -354,6 +626,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn test_ferragosto() {
         let provider = Augurando::default();
         let urls = provider.scrape(Greeting::Ferragosto).await.unwrap();
@@ -361,6 +634,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn test_halloween() {
         let provider = Augurando::default();
         let urls = provider.scrape(Greeting::Halloween).await.unwrap();
@@ -368,6 +642,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn test_ognissanti() {
         let provider = Augurando::default();
         let urls = provider.scrape(Greeting::Ognissanti).await.unwrap();
@@ -375,6 +650,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn test_defunti() {
         let provider = Augurando::default();
         let urls = provider.scrape(Greeting::Defunti).await.unwrap();
@@ -382,6 +658,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn test_immacolata_concenzione() {
         let provider = Augurando::default();
         let urls = provider
@@ -392,6 +669,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn test_san_nicola() {
         let provider = Augurando::default();
         let urls = provider.scrape(Greeting::SanNicola).await.unwrap();
@@ -399,6 +677,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn test_sant_ambrogio() {
         let provider = Augurando::default();
         let urls = provider.scrape(Greeting::SantAmbrogio).await.unwrap();
@@ -406,6 +685,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn test_natale() {
         let provider = Augurando::default();
         let urls = provider.scrape(Greeting::Natale).await.unwrap();
@@ -413,6 +693,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn test_santo_stefano() {
         let provider = Augurando::default();
         let urls = provider.scrape(Greeting::SantoStefano).await.unwrap();
@@ -420,6 +701,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn test_san_silvestro() {
         let provider = Augurando::default();
         let urls = provider.scrape(Greeting::SanSilvestro).await.unwrap();
@@ -2,47 +2,54 @@
 //!
 //! This provider provides images from <https://www.buongiornissimocaffe.it/>
 
-use std::str::FromStr;
-
 use async_trait::async_trait;
 use chrono::Weekday;
 use const_format::concatcp;
+use reqwest::Client;
 use scraper::{Html, Selector};
 
-use super::{Greeting, Scrape, ScrapeError, ScrapeResult, Url};
-
-const BASE_URL: &str = "https://www.buongiornissimocaffe.it/category";
-const BUONGIORNO_URL: &str = concatcp!(BASE_URL, "/immagini-buongiorno/");
-const BUONGIORNO_WEEKDAY_BASE_URL: &str = concatcp!(BASE_URL, "/buon-");
-const BUONA_DOMENICA_URL: &str = concatcp!(BASE_URL, "/buona-domenica/");
-const BUONA_NOTTE_URL: &str = concatcp!(BASE_URL, "/immagini-buonanotte/");
-const BUONA_SERATA_URL: &str = concatcp!(BASE_URL, "/buona-serata/");
+use super::{
+    Greeting, HttpClient, Pagination, RateLimit, RetryPolicy, RobotsTxt, Scrape, ScrapeError,
+    ScrapeResult, ScrapedImage, SourcePolicy, Url,
+};
+
+const PROVIDER_NAME: &str = "BuongiornissimoCaffe";
+const MAIN_SELECTOR: &str = "main";
+const MASONRY_SELECTOR: &str = r#"div[class="masonry"]"#;
+
+const DEFAULT_BASE_URL: &str = "https://www.buongiornissimocaffe.it";
+const CATEGORY_PATH: &str = "/category";
+const BUONGIORNO_PATH: &str = concatcp!(CATEGORY_PATH, "/immagini-buongiorno/");
+const BUONGIORNO_WEEKDAY_BASE_PATH: &str = concatcp!(CATEGORY_PATH, "/buon-");
+const BUONA_DOMENICA_PATH: &str = concatcp!(CATEGORY_PATH, "/buona-domenica/");
+const BUONA_NOTTE_PATH: &str = concatcp!(CATEGORY_PATH, "/immagini-buonanotte/");
+const BUONA_SERATA_PATH: &str = concatcp!(CATEGORY_PATH, "/buona-serata/");
 // festività
-const CAPODANNO_URL: &str = concatcp!(BASE_URL, "/buon-anno/");
-const BEFANA_URL: &str = concatcp!(BASE_URL, "/befana/");
-const SAN_VALENTINO_URL: &str = concatcp!(BASE_URL, "/auguri-san-valentino/");
-const GIOVEDI_GRASSO_URL: &str = concatcp!(BASE_URL, "/buon-giovedi-grasso/");
-const MARTEDI_GRASSO_URL: &str = concatcp!(BASE_URL, "/buon-martedi-grasso/");
-const FESTA_DELLE_DONNE: &str = concatcp!(BASE_URL, "/auguri-festa-delle-donne/");
-const PALME_URL: &str = concatcp!(BASE_URL, "/buona-domenica-delle-palme/");
-const PASQUA_URL: &str = concatcp!(BASE_URL, "/buona-pasqua/");
-const PASQUETTA_URL: &str = concatcp!(BASE_URL, "/pasquetta/");
-const LIBERAZIONE_URL: &str = concatcp!(BASE_URL, "/buon-25-aprile/");
-const PRIMO_MAGGIO_URL: &str = concatcp!(BASE_URL, "/buon-1-maggio/");
-const HALLOWEEN_URL: &str = concatcp!(BASE_URL, "/halloween/");
-const OGNISSANTI_URL: &str = concatcp!(BASE_URL, "/ognissanti/");
-const DEFUNTI_URL: &str = concatcp!(BASE_URL, "/commemorazione-dei-defunti/");
-const IMMACOLATA_CONCEZIONE_URL: &str = concatcp!(BASE_URL, "/8-dicembre/");
-const VIGILIA_URL: &str = concatcp!(BASE_URL, "/24-dicembre/");
-const NATALE_URL: &str = concatcp!(BASE_URL, "/buon-natale/");
-const SANTO_STEFANO_URL: &str = concatcp!(BASE_URL, "/santo-stefano/");
+const CAPODANNO_PATH: &str = concatcp!(CATEGORY_PATH, "/buon-anno/");
+const BEFANA_PATH: &str = concatcp!(CATEGORY_PATH, "/befana/");
+const SAN_VALENTINO_PATH: &str = concatcp!(CATEGORY_PATH, "/auguri-san-valentino/");
+const GIOVEDI_GRASSO_PATH: &str = concatcp!(CATEGORY_PATH, "/buon-giovedi-grasso/");
+const MARTEDI_GRASSO_PATH: &str = concatcp!(CATEGORY_PATH, "/buon-martedi-grasso/");
+const FESTA_DELLE_DONNE_PATH: &str = concatcp!(CATEGORY_PATH, "/auguri-festa-delle-donne/");
+const PALME_PATH: &str = concatcp!(CATEGORY_PATH, "/buona-domenica-delle-palme/");
+const PASQUA_PATH: &str = concatcp!(CATEGORY_PATH, "/buona-pasqua/");
+const PASQUETTA_PATH: &str = concatcp!(CATEGORY_PATH, "/pasquetta/");
+const LIBERAZIONE_PATH: &str = concatcp!(CATEGORY_PATH, "/buon-25-aprile/");
+const PRIMO_MAGGIO_PATH: &str = concatcp!(CATEGORY_PATH, "/buon-1-maggio/");
+const HALLOWEEN_PATH: &str = concatcp!(CATEGORY_PATH, "/halloween/");
+const OGNISSANTI_PATH: &str = concatcp!(CATEGORY_PATH, "/ognissanti/");
+const DEFUNTI_PATH: &str = concatcp!(CATEGORY_PATH, "/commemorazione-dei-defunti/");
+const IMMACOLATA_CONCEZIONE_PATH: &str = concatcp!(CATEGORY_PATH, "/8-dicembre/");
+const VIGILIA_PATH: &str = concatcp!(CATEGORY_PATH, "/24-dicembre/");
+const NATALE_PATH: &str = concatcp!(CATEGORY_PATH, "/buon-natale/");
+const SANTO_STEFANO_PATH: &str = concatcp!(CATEGORY_PATH, "/santo-stefano/");
 
 /// Buongiornissimo provider which scrapes images from <https://www.buongiornissimocaffe.it>.
 ///
-/// Supported [`Greeting`]s:
+/// Supported [`Greeting`]s (see also [`Scrape::supports`]):
 ///
 /// - [`Greeting::BuonGiorno`]
-/// - [`Greeting::BuonGiornoWeekday`]
+/// - [`Greeting::BuonGiornoWeekday`] (every weekday)
 /// - [`Greeting::BuonaSerata`]
 /// - [`Greeting::BuonaNotte`]
 /// - [`Greeting::Capodanno`]
@@ -63,10 +70,77 @@ const SANTO_STEFANO_URL: &str = concatcp!(BASE_URL, "/santo-stefano/");
 /// - [`Greeting::VigiliaDiNatale`]
 /// - [`Greeting::Natale`]
 /// - [`Greeting::SantoStefano`]
-#[derive(Default)]
-pub struct BuongiornissimoCaffe;
+#[derive(Debug, Clone)]
+pub struct BuongiornissimoCaffe {
+    http: HttpClient,
+    base_url: Url,
+    pagination: Pagination,
+}
+
+impl Default for BuongiornissimoCaffe {
+    fn default() -> Self {
+        Self {
+            http: HttpClient::new(PROVIDER_NAME),
+            base_url: Url::parse(DEFAULT_BASE_URL).expect("invalid default base url"),
+            pagination: Pagination::default(),
+        }
+    }
+}
 
 impl BuongiornissimoCaffe {
+    /// Use the provided [`Client`] to send the requests, instead of a default one.
+    ///
+    /// This allows to share a single tuned client (connection pool, timeouts, proxy, user-agent...) between providers.
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.http.set_client(client);
+        self
+    }
+
+    /// Retry the requests which fail for a transient error, as described by `retry`.
+    ///
+    /// By default requests are not retried.
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.http.set_retry(retry);
+        self
+    }
+
+    /// Limit the rate of the requests sent to the provider host, as described by `rate_limit`.
+    ///
+    /// The limit is shared with all the other providers sending requests to the same host.
+    pub fn with_rate_limit(mut self, rate_limit: RateLimit) -> Self {
+        self.http.set_rate_limit(rate_limit);
+        self
+    }
+
+    /// Comply with the `robots.txt` of the provider site, as described by `robots`.
+    ///
+    /// Disallowed pages are not requested and [`ScrapeError::RobotsDisallowed`] is returned instead.
+    pub fn with_robots_txt(mut self, robots: RobotsTxt) -> Self {
+        self.http.set_robots_txt(robots);
+        self
+    }
+
+    /// Use the provided base url to build the pages urls, instead of <https://www.buongiornissimocaffe.it>.
+    ///
+    /// The per-greeting paths are appended to the base url, so this can be used to point the provider to a mirror or to a local server.
+    pub fn with_base_url(mut self, base_url: Url) -> Self {
+        self.base_url = base_url;
+        self
+    }
+
+    /// Crawl the following pages of the category listing, as described by `pagination`, instead of the first one only.
+    pub fn with_pagination(mut self, pagination: Pagination) -> Self {
+        self.pagination = pagination;
+        self
+    }
+
+    fn get_url(&self, greeting: Greeting) -> ScrapeResult<Url> {
+        Self::get_path(greeting).map(|path| {
+            let url = format!("{}{}", self.base_url.as_str().trim_end_matches('/'), path);
+            Url::parse(&url).expect("invalid page url")
+        })
+    }
+
     fn weekday(weekday: chrono::Weekday) -> &'static str {
         match weekday {
             Weekday::Mon => "lunedi",
@@ -79,97 +153,116 @@ impl BuongiornissimoCaffe {
         }
     }
 
-    fn get_url(greeting: Greeting) -> ScrapeResult<String> {
+    fn get_path(greeting: Greeting) -> ScrapeResult<String> {
         match greeting {
-            Greeting::BuonGiorno => Ok(BUONGIORNO_URL.to_string()),
-            Greeting::BuonGiornoWeekday(Weekday::Sun) => Ok(BUONA_DOMENICA_URL.to_string()),
+            Greeting::BuonGiorno => Ok(BUONGIORNO_PATH.to_string()),
+            Greeting::BuonGiornoWeekday(Weekday::Sun) => Ok(BUONA_DOMENICA_PATH.to_string()),
             Greeting::BuonGiornoWeekday(weekday) => Ok(format!(
                 "{}{}/",
-                BUONGIORNO_WEEKDAY_BASE_URL,
+                BUONGIORNO_WEEKDAY_BASE_PATH,
                 Self::weekday(weekday)
             )),
-            Greeting::BuonaSerata => Ok(BUONA_SERATA_URL.to_string()),
-            Greeting::BuonaNotte => Ok(BUONA_NOTTE_URL.to_string()),
-            Greeting::Capodanno => Ok(CAPODANNO_URL.to_string()),
-            Greeting::Epifania => Ok(BEFANA_URL.to_string()),
-            Greeting::SanValentino => Ok(SAN_VALENTINO_URL.to_string()),
-            Greeting::GiovediGrasso => Ok(GIOVEDI_GRASSO_URL.to_string()),
-            Greeting::MartediGrasso => Ok(MARTEDI_GRASSO_URL.to_string()),
-            Greeting::FestaDelleDonne => Ok(FESTA_DELLE_DONNE.to_string()),
-            Greeting::DomenicaDellePalme => Ok(PALME_URL.to_string()),
-            Greeting::Pasqua => Ok(PASQUA_URL.to_string()),
-            Greeting::Pasquetta => Ok(PASQUETTA_URL.to_string()),
-            Greeting::Liberazione => Ok(LIBERAZIONE_URL.to_string()),
-            Greeting::FestaDeiLavoratori => Ok(PRIMO_MAGGIO_URL.to_string()),
-            Greeting::Halloween => Ok(HALLOWEEN_URL.to_string()),
-            Greeting::Ognissanti => Ok(OGNISSANTI_URL.to_string()),
-            Greeting::Defunti => Ok(DEFUNTI_URL.to_string()),
-            Greeting::ImmacolataConcenzione => Ok(IMMACOLATA_CONCEZIONE_URL.to_string()),
-            Greeting::VigiliaDiNatale => Ok(VIGILIA_URL.to_string()),
-            Greeting::Natale => Ok(NATALE_URL.to_string()),
-            Greeting::SantoStefano => Ok(SANTO_STEFANO_URL.to_string()),
+            Greeting::BuonaSerata => Ok(BUONA_SERATA_PATH.to_string()),
+            Greeting::BuonaNotte => Ok(BUONA_NOTTE_PATH.to_string()),
+            Greeting::Capodanno => Ok(CAPODANNO_PATH.to_string()),
+            Greeting::Epifania => Ok(BEFANA_PATH.to_string()),
+            Greeting::SanValentino => Ok(SAN_VALENTINO_PATH.to_string()),
+            Greeting::GiovediGrasso => Ok(GIOVEDI_GRASSO_PATH.to_string()),
+            Greeting::MartediGrasso => Ok(MARTEDI_GRASSO_PATH.to_string()),
+            Greeting::FestaDelleDonne => Ok(FESTA_DELLE_DONNE_PATH.to_string()),
+            Greeting::DomenicaDellePalme => Ok(PALME_PATH.to_string()),
+            Greeting::Pasqua => Ok(PASQUA_PATH.to_string()),
+            Greeting::Pasquetta => Ok(PASQUETTA_PATH.to_string()),
+            Greeting::Liberazione => Ok(LIBERAZIONE_PATH.to_string()),
+            Greeting::FestaDeiLavoratori => Ok(PRIMO_MAGGIO_PATH.to_string()),
+            Greeting::Halloween => Ok(HALLOWEEN_PATH.to_string()),
+            Greeting::Ognissanti => Ok(OGNISSANTI_PATH.to_string()),
+            Greeting::Defunti => Ok(DEFUNTI_PATH.to_string()),
+            Greeting::ImmacolataConcenzione => Ok(IMMACOLATA_CONCEZIONE_PATH.to_string()),
+            Greeting::VigiliaDiNatale => Ok(VIGILIA_PATH.to_string()),
+            Greeting::Natale => Ok(NATALE_PATH.to_string()),
+            Greeting::SantoStefano => Ok(SANTO_STEFANO_PATH.to_string()),
             _ => Err(ScrapeError::UnsupportedGreeting),
         }
     }
-}
 
-#[async_trait]
-impl Scrape for BuongiornissimoCaffe {
-    async fn scrape(&self, greeting: Greeting) -> ScrapeResult<Vec<Url>> {
-        let url = Self::get_url(greeting)?;
-        debug!("scraping greeting of kind {:?} at {}", greeting, url);
-        // send request
-        let body = reqwest::get(&url).await?.text().await?;
-        debug!("got body of length {}", body.len());
+    /// Parse the html page of the provider, located at `page_url`, and return the urls of the images found in it.
+    ///
+    /// This can be used to scrape pages which have been fetched by other means.
+    pub fn parse_html(html: &str, page_url: &Url) -> ScrapeResult<Vec<Url>> {
+        Self::parse_images(html, page_url)
+            .map(|images| images.into_iter().map(|image| image.url).collect())
+    }
+
+    /// Parse the html page of the provider, located at `page_url`, and return the images found in it.
+    ///
+    /// This is what [`Scrape::scrape_images`] runs on the fetched page.
+    pub fn parse_images(html: &str, page_url: &Url) -> ScrapeResult<Vec<ScrapedImage>> {
         // parse document
-        let document = Html::parse_document(&body);
+        let document = Html::parse_document(html);
         debug!("html document parsed");
         // search for entry content selector
-        let main_selector = Selector::parse(r#"main"#).unwrap();
+        let main_selector = Selector::parse(MAIN_SELECTOR).unwrap();
         let mut main_elements = document.select(&main_selector);
         let main = main_elements.next();
         if main.is_none() {
             error!("main is none");
-            return Err(ScrapeError::UnexpectedHtml(
-                "could not find a div with class entry-content".to_string(),
-            ));
+            return Err(ScrapeError::UnexpectedHtml {
+                provider: PROVIDER_NAME.to_string(),
+                url: Box::new(page_url.clone()),
+                selector: MAIN_SELECTOR.to_string(),
+            });
         }
-        debug!("searching for entry-content div");
-        let entry_content_selector = Selector::parse(r#"div[class="masonry"]"#).unwrap();
+        debug!("searching for masonry div");
+        let entry_content_selector = Selector::parse(MASONRY_SELECTOR).unwrap();
         let mut containers = main.unwrap().select(&entry_content_selector);
         debug!("getting first container");
         let container = containers.next();
         if container.is_none() {
             error!("container is none");
-            return Err(ScrapeError::UnexpectedHtml(
-                "could not find a div with class entry-content".to_string(),
-            ));
+            return Err(ScrapeError::UnexpectedHtml {
+                provider: PROVIDER_NAME.to_string(),
+                url: Box::new(page_url.clone()),
+                selector: MASONRY_SELECTOR.to_string(),
+            });
         }
         let thumbnail_selector = Selector::parse(r#"div[class="thumbnail"]"#).unwrap();
         let thumbnails = container.unwrap().select(&thumbnail_selector);
-        let mut urls: Vec<Url> = Vec::new();
+        let mut images = Vec::new();
         // search images in thumbnails
         for thumbnail in thumbnails {
             debug!("selecting images in thumbnail");
             let img_selector = Selector::parse("img").unwrap();
-            let images = thumbnail.select(&img_selector);
-            for image in images {
-                if let Some(Ok(url)) = image
-                    .value()
-                    .attr("src")
-                    .filter(|s| s.starts_with("http") || s.starts_with("https"))
-                    .map(Url::from_str)
+            let elements = thumbnail.select(&img_selector);
+            for element in elements {
+                if let Some(image) =
+                    ScrapedImage::from_element(element, SourcePolicy::Any, page_url, PROVIDER_NAME)
                 {
-                    debug!("found image with url {}", url);
-                    urls.push(url)
+                    debug!("found image with url {}", image.url);
+                    images.push(image)
                 }
             }
         }
-        if urls.is_empty() {
-            error!("urls is empty");
-            return Err(ScrapeError::NoImages);
+        if images.is_empty() {
+            error!("images is empty");
+            return Err(ScrapeError::no_images(PROVIDER_NAME, page_url));
         }
-        Ok(urls)
+        Ok(images)
+    }
+}
+
+#[async_trait]
+impl Scrape for BuongiornissimoCaffe {
+    async fn scrape_images(&self, greeting: Greeting) -> ScrapeResult<Vec<ScrapedImage>> {
+        let url = self.get_url(greeting)?;
+        debug!("scraping greeting of kind {:?} at {}", greeting, url);
+        self.pagination
+            .crawl(&self.http, url, Self::parse_images)
+            .await
+    }
+
+    fn supports(&self, greeting: Greeting) -> bool {
+        Self::get_path(greeting).is_ok()
     }
 }
 
@@ -179,9 +272,234 @@ mod test {
     use pretty_assertions::assert_eq;
 
     use super::*;
+    use crate::SrcsetCandidate;
+    use crate::test_server::{Response, TestServer};
+
+    const IMAGES_FIXTURE: &str =
+        include_str!("../../tests/fixtures/buongiornissimo_caffe/category.html");
+    const NEXT_PAGE_FIXTURE: &str =
+        include_str!("../../tests/fixtures/buongiornissimo_caffe/category_page_2.html");
+    const NO_IMAGES_FIXTURE: &str =
+        include_str!("../../tests/fixtures/buongiornissimo_caffe/no_images.html");
+    const NO_CONTENT_FIXTURE: &str = include_str!("../../tests/fixtures/no_content.html");
+
+    fn page_url() -> Url {
+        Url::parse("https://www.buongiornissimocaffe.it/category/immagini-buongiorno/").unwrap()
+    }
+
+    #[test]
+    fn should_get_url_with_default_base_url() {
+        assert_eq!(
+            BuongiornissimoCaffe::default()
+                .get_url(Greeting::BuonGiorno)
+                .unwrap()
+                .as_str(),
+            "https://www.buongiornissimocaffe.it/category/immagini-buongiorno/"
+        );
+    }
+
+    #[test]
+    fn should_get_url_with_custom_base_url() {
+        let provider = BuongiornissimoCaffe::default()
+            .with_base_url(Url::parse("http://localhost:8080/mirror/").unwrap());
+        assert_eq!(
+            provider.get_url(Greeting::BuonGiorno).unwrap().as_str(),
+            "http://localhost:8080/mirror/category/immagini-buongiorno/"
+        );
+    }
+
+    #[test]
+    fn should_tell_supported_greetings() {
+        let provider = BuongiornissimoCaffe::default();
+        assert_eq!(
+            provider.supported_greetings().collect::<Vec<_>>(),
+            vec![
+                Greeting::BuonGiorno,
+                Greeting::BuonGiornoWeekday(Weekday::Mon),
+                Greeting::BuonGiornoWeekday(Weekday::Tue),
+                Greeting::BuonGiornoWeekday(Weekday::Wed),
+                Greeting::BuonGiornoWeekday(Weekday::Thu),
+                Greeting::BuonGiornoWeekday(Weekday::Fri),
+                Greeting::BuonGiornoWeekday(Weekday::Sat),
+                Greeting::BuonGiornoWeekday(Weekday::Sun),
+                Greeting::BuonaNotte,
+                Greeting::BuonaSerata,
+                Greeting::Capodanno,
+                Greeting::Epifania,
+                Greeting::SanValentino,
+                Greeting::GiovediGrasso,
+                Greeting::MartediGrasso,
+                Greeting::FestaDelleDonne,
+                Greeting::DomenicaDellePalme,
+                Greeting::Pasqua,
+                Greeting::Pasquetta,
+                Greeting::Liberazione,
+                Greeting::FestaDeiLavoratori,
+                Greeting::Halloween,
+                Greeting::Ognissanti,
+                Greeting::Defunti,
+                Greeting::ImmacolataConcenzione,
+                Greeting::VigiliaDiNatale,
+                Greeting::Natale,
+                Greeting::SantoStefano,
+            ]
+        );
+    }
+
+    #[test]
+    fn should_parse_images_from_html() {
+        assert_eq!(
+            BuongiornissimoCaffe::parse_html(IMAGES_FIXTURE, &page_url()).unwrap(),
+            vec![
+                Url::parse("https://www.buongiornissimocaffe.it/wp-content/uploads/2024/10/buongiorno-caffe-e-biscotti.jpg").unwrap(),
+                Url::parse("https://www.buongiornissimocaffe.it/wp-content/uploads/2024/10/buongiorno-con-le-rose.jpg").unwrap(),
+                Url::parse("https://www.buongiornissimocaffe.it/wp-content/uploads/2024/09/buongiornissimo-kaffe.png").unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_parse_images_metadata_from_html() {
+        let images = BuongiornissimoCaffe::parse_images(IMAGES_FIXTURE, &page_url()).unwrap();
+        assert_eq!(
+            images[0],
+            ScrapedImage {
+                url: Url::parse("https://www.buongiornissimocaffe.it/wp-content/uploads/2024/10/buongiorno-caffe-e-biscotti.jpg").unwrap(),
+                alt: Some("Buongiorno caffè e biscotti".to_string()),
+                width: Some(600),
+                height: Some(600),
+                srcset: vec![
+                    SrcsetCandidate {
+                        url: Url::parse("https://www.buongiornissimocaffe.it/wp-content/uploads/2024/10/buongiorno-caffe-e-biscotti-300x300.jpg").unwrap(),
+                        descriptor: Some("300w".to_string()),
+                    },
+                    SrcsetCandidate {
+                        url: Url::parse("https://www.buongiornissimocaffe.it/wp-content/uploads/2024/10/buongiorno-caffe-e-biscotti.jpg").unwrap(),
+                        descriptor: Some("600w".to_string()),
+                    },
+                ],
+                page_url: page_url(),
+                provider: "BuongiornissimoCaffe".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn should_not_parse_html_without_main() {
+        assert_eq!(
+            BuongiornissimoCaffe::parse_html(NO_CONTENT_FIXTURE, &page_url()).unwrap_err(),
+            ScrapeError::UnexpectedHtml {
+                provider: "BuongiornissimoCaffe".to_string(),
+                url: Box::new(page_url()),
+                selector: "main".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn should_not_parse_html_without_masonry() {
+        assert_eq!(
+            BuongiornissimoCaffe::parse_html(
+                include_str!("../../tests/fixtures/buongiornissimo_caffe/no_masonry.html"),
+                &page_url()
+            )
+            .unwrap_err(),
+            ScrapeError::UnexpectedHtml {
+                provider: "BuongiornissimoCaffe".to_string(),
+                url: Box::new(page_url()),
+                selector: r#"div[class="masonry"]"#.to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn should_not_parse_html_without_images() {
+        assert_eq!(
+            BuongiornissimoCaffe::parse_html(NO_IMAGES_FIXTURE, &page_url()).unwrap_err(),
+            ScrapeError::no_images(PROVIDER_NAME, &page_url())
+        );
+    }
+
+    #[tokio::test]
+    async fn should_scrape_images_from_base_url() {
+        let server = TestServer::serve(IMAGES_FIXTURE).await;
+        let urls = BuongiornissimoCaffe::default()
+            .with_base_url(server.url())
+            .scrape(Greeting::BuonGiorno)
+            .await
+            .unwrap();
+        assert_eq!(urls.len(), 3);
+        assert_eq!(server.requests()[0].path, "/category/immagini-buongiorno/");
+    }
 
     #[tokio::test]
+    async fn should_crawl_following_pages() {
+        let server = TestServer::start(|request| match request.path.as_str() {
+            "/category/immagini-buongiorno/page/2/" => Response::ok(NEXT_PAGE_FIXTURE),
+            _ => Response::ok(IMAGES_FIXTURE),
+        })
+        .await;
+        let provider = BuongiornissimoCaffe::default()
+            .with_base_url(server.url())
+            .with_pagination(Pagination::default().with_max_pages(5));
+        let urls = provider.scrape(Greeting::BuonGiorno).await.unwrap();
 
+        // duplicated images are returned once
+        assert_eq!(urls.len(), 4);
+        assert_eq!(
+            urls[3].as_str(),
+            "https://www.buongiornissimocaffe.it/wp-content/uploads/2024/08/buongiorno-tazzina-fumante.jpg"
+        );
+        let paths: Vec<String> = server
+            .requests()
+            .into_iter()
+            .map(|request| request.path)
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                "/category/immagini-buongiorno/",
+                "/category/immagini-buongiorno/page/2/"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn should_not_scrape_greeting_disallowed_by_robots_txt() {
+        let server = TestServer::start(|request| match request.path.as_str() {
+            "/robots.txt" => Response::ok("User-agent: *\nDisallow: /category/buon-natale/"),
+            _ => Response::ok(IMAGES_FIXTURE),
+        })
+        .await;
+        let provider = BuongiornissimoCaffe::default()
+            .with_base_url(server.url())
+            .with_robots_txt(RobotsTxt::new("buongiornissimo-rs"));
+
+        assert!(provider.scrape(Greeting::BuonGiorno).await.is_ok());
+        assert!(matches!(
+            provider.scrape(Greeting::Natale).await,
+            Err(ScrapeError::RobotsDisallowed { .. })
+        ));
+        assert!(
+            server
+                .requests()
+                .iter()
+                .all(|request| request.path != "/category/buon-natale/")
+        );
+    }
+
+    #[tokio::test]
+    async fn should_return_http_error_if_site_is_unreachable() {
+        let provider = BuongiornissimoCaffe::default()
+            .with_base_url(Url::parse("http://127.0.0.1:1/").unwrap());
+        assert!(matches!(
+            provider.scrape(Greeting::BuonGiorno).await,
+            Err(ScrapeError::Connect { .. })
+        ));
+    }
+
+    #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn should_get_goodmorning_images() {
         assert!(
             !BuongiornissimoCaffe::default()
@@ -193,6 +511,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn should_get_weekday_images() {
         assert!(
             !BuongiornissimoCaffe::default()
@@ -246,6 +565,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn should_scrape_for_greeting_buona_notte() {
         assert!(
             !BuongiornissimoCaffe::default()
@@ -257,6 +577,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn should_scrape_for_greeting_capodanno() {
         assert!(
             !BuongiornissimoCaffe::default()
@@ -268,6 +589,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn should_scrape_for_greeting_epifania() {
         assert!(
             !BuongiornissimoCaffe::default()
@@ -279,6 +601,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn should_scrape_for_greeting_san_valentino() {
         assert!(
             !BuongiornissimoCaffe::default()
@@ -290,6 +613,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn should_scrape_for_greeting_giovedi_grasso() {
         assert!(
             !BuongiornissimoCaffe::default()
@@ -301,6 +625,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn should_scrape_for_greeting_martedi_grasso() {
         assert!(
             !BuongiornissimoCaffe::default()
@@ -312,6 +637,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn should_scrape_for_greeting_festa_delle_donne() {
         assert!(
             !BuongiornissimoCaffe::default()
@@ -323,6 +649,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn should_scrape_for_greeting_domenica_delle_palme() {
         assert!(
             !BuongiornissimoCaffe::default()
@@ -334,6 +661,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn should_scrape_for_greeting_pasqua() {
         assert!(
             !BuongiornissimoCaffe::default()
@@ -345,6 +673,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn should_scrape_for_greeting_pasquetta() {
         assert!(
             !BuongiornissimoCaffe::default()
@@ -356,6 +685,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn should_scrape_for_greeting_liberazione() {
         assert!(
             !BuongiornissimoCaffe::default()
@@ -367,6 +697,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn should_scrape_for_greeting_festa_dei_lavoratori() {
         assert!(
             !BuongiornissimoCaffe::default()
@@ -378,6 +709,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn should_scrape_for_greeting_halloween() {
         assert!(
             !BuongiornissimoCaffe::default()
@@ -389,6 +721,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn should_scrape_for_greeting_ognissanti() {
         assert!(
             !BuongiornissimoCaffe::default()
@@ -400,6 +733,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn should_scrape_for_greeting_defunti() {
         assert!(
             !BuongiornissimoCaffe::default()
@@ -411,6 +745,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn should_scrape_for_greeting_immacolata_concenzione() {
         assert!(
             !BuongiornissimoCaffe::default()
@@ -422,6 +757,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn should_scrape_for_greeting_vigilia_di_natale() {
         assert!(
             !BuongiornissimoCaffe::default()
@@ -433,6 +769,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn should_scrape_for_greeting_natale() {
         assert!(
             !BuongiornissimoCaffe::default()
@@ -444,6 +781,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn should_scrape_for_greeting_santo_stefano() {
         assert!(
             !BuongiornissimoCaffe::default()
@@ -2,112 +2,199 @@
 //!
 //! This provider provides images from <https://buongiornoimmagini.it/>
 
-use std::str::FromStr;
-
 use async_trait::async_trait;
 use chrono::Weekday;
-use const_format::concatcp;
+use reqwest::Client;
 use scraper::{Html, Selector};
 
-use super::{Greeting, Scrape, ScrapeError, ScrapeResult, Url};
+use super::{
+    Greeting, HttpClient, Pagination, RateLimit, RetryPolicy, RobotsTxt, Scrape, ScrapeError,
+    ScrapeResult, ScrapedImage, SourcePolicy, Url,
+};
+
+const PROVIDER_NAME: &str = "BuongiornoImmagini";
+const MAIN_SELECTOR: &str = r#"main"#;
 
-const BASE_URL: &str = "https://buongiornoimmagini.it";
-const BUONGIORNO_URL: &str = concatcp!(BASE_URL, "/immagini-buongiorno/");
+const DEFAULT_BASE_URL: &str = "https://buongiornoimmagini.it";
+const BUONGIORNO_PATH: &str = "/immagini-buongiorno/";
 
 // weekdays
-const BUONGIORNO_LUNEDI_URL: &str = concatcp!(BASE_URL, "/buon-giorno-lunedi/");
-const BUONGIORNO_MARTEDI_URL: &str = concatcp!(BASE_URL, "/buon-giorno-buon-martedi/");
-const BUONGIORNO_MERCOLEDI_URL: &str = concatcp!(BASE_URL, "/buon-giorno-mercoledi/");
-const BUONGIORNO_GIOVEDI_URL: &str = concatcp!(BASE_URL, "/buon-giorno-buon-giovedi/");
-const BUONGIORNO_VENERDI_URL: &str = concatcp!(BASE_URL, "/buon-giorno-buon-venerdi/");
-const BUONGIORNO_SABATO_URL: &str = concatcp!(BASE_URL, "/buon-giorno-buon-sabato/");
-const BUONGIORNO_DOMENICA_URL: &str = concatcp!(BASE_URL, "/buona-domenica/");
-const WEEKEND_URL: &str = concatcp!(BASE_URL, "/buon-weekend/");
-
-const BUON_PRANZO_URL: &str = concatcp!(BASE_URL, "/buon-pranzo/");
-const BUONA_NOTTE_URL: &str = concatcp!(BASE_URL, "/buonanotte/");
-const BUONA_SERATA_URL: &str = concatcp!(BASE_URL, "/buona-serata/");
-const BUONA_CENA_URL: &str = concatcp!(BASE_URL, "/buona-cena/");
+const BUONGIORNO_LUNEDI_PATH: &str = "/buon-giorno-lunedi/";
+const BUONGIORNO_MARTEDI_PATH: &str = "/buon-giorno-buon-martedi/";
+const BUONGIORNO_MERCOLEDI_PATH: &str = "/buon-giorno-mercoledi/";
+const BUONGIORNO_GIOVEDI_PATH: &str = "/buon-giorno-buon-giovedi/";
+const BUONGIORNO_VENERDI_PATH: &str = "/buon-giorno-buon-venerdi/";
+const BUONGIORNO_SABATO_PATH: &str = "/buon-giorno-buon-sabato/";
+const BUONGIORNO_DOMENICA_PATH: &str = "/buona-domenica/";
+const WEEKEND_PATH: &str = "/buon-weekend/";
+
+const BUON_PRANZO_PATH: &str = "/buon-pranzo/";
+const BUONA_NOTTE_PATH: &str = "/buonanotte/";
+const BUONA_SERATA_PATH: &str = "/buona-serata/";
+const BUONA_CENA_PATH: &str = "/buona-cena/";
 
 /// Buongiornissimo provider which scrapes images from <https://buongiornoimmagini.it>
 ///
-/// Supported [`Greeting`]s:
+/// Supported [`Greeting`]s (see also [`Scrape::supports`]):
 ///
 /// - [`Greeting::BuonGiorno`]
-/// - [`Greeting::BuonGiornoWeekday`]
+/// - [`Greeting::BuonGiornoWeekday`] (every weekday)
 /// - [`Greeting::BuonPranzo`]
 /// - [`Greeting::BuonaNotte`]
 /// - [`Greeting::BuonaSerata`]
 /// - [`Greeting::BuonaCena`]
 /// - [`Greeting::Weekend`]
-#[derive(Default)]
-pub struct BuongiornoImmagini;
+#[derive(Debug, Clone)]
+pub struct BuongiornoImmagini {
+    http: HttpClient,
+    base_url: Url,
+    pagination: Pagination,
+}
+
+impl Default for BuongiornoImmagini {
+    fn default() -> Self {
+        Self {
+            http: HttpClient::new(PROVIDER_NAME),
+            base_url: Url::parse(DEFAULT_BASE_URL).expect("invalid default base url"),
+            pagination: Pagination::default(),
+        }
+    }
+}
 
 impl BuongiornoImmagini {
-    fn get_url(greeting: Greeting) -> ScrapeResult<String> {
+    /// Use the provided [`Client`] to send the requests, instead of a default one.
+    ///
+    /// This allows to share a single tuned client (connection pool, timeouts, proxy, user-agent...) between providers.
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.http.set_client(client);
+        self
+    }
+
+    /// Retry the requests which fail for a transient error, as described by `retry`.
+    ///
+    /// By default requests are not retried.
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.http.set_retry(retry);
+        self
+    }
+
+    /// Limit the rate of the requests sent to the provider host, as described by `rate_limit`.
+    ///
+    /// The limit is shared with all the other providers sending requests to the same host.
+    pub fn with_rate_limit(mut self, rate_limit: RateLimit) -> Self {
+        self.http.set_rate_limit(rate_limit);
+        self
+    }
+
+    /// Comply with the `robots.txt` of the provider site, as described by `robots`.
+    ///
+    /// Disallowed pages are not requested and [`ScrapeError::RobotsDisallowed`] is returned instead.
+    pub fn with_robots_txt(mut self, robots: RobotsTxt) -> Self {
+        self.http.set_robots_txt(robots);
+        self
+    }
+
+    /// Use the provided base url to build the pages urls, instead of <https://buongiornoimmagini.it>.
+    ///
+    /// The per-greeting paths are appended to the base url, so this can be used to point the provider to a mirror or to a local server.
+    pub fn with_base_url(mut self, base_url: Url) -> Self {
+        self.base_url = base_url;
+        self
+    }
+
+    /// Crawl the following pages of the article, as described by `pagination`, instead of the first one only.
+    pub fn with_pagination(mut self, pagination: Pagination) -> Self {
+        self.pagination = pagination;
+        self
+    }
+
+    fn get_url(&self, greeting: Greeting) -> ScrapeResult<Url> {
+        Self::get_path(greeting).map(|path| {
+            let url = format!("{}{}", self.base_url.as_str().trim_end_matches('/'), path);
+            Url::parse(&url).expect("invalid page url")
+        })
+    }
+
+    fn get_path(greeting: Greeting) -> ScrapeResult<String> {
         match greeting {
-            Greeting::BuonGiorno => Ok(BUONGIORNO_URL.to_string()),
-            Greeting::BuonGiornoWeekday(Weekday::Sun) => Ok(BUONGIORNO_DOMENICA_URL.to_string()),
-            Greeting::BuonGiornoWeekday(Weekday::Mon) => Ok(BUONGIORNO_LUNEDI_URL.to_string()),
-            Greeting::BuonGiornoWeekday(Weekday::Tue) => Ok(BUONGIORNO_MARTEDI_URL.to_string()),
-            Greeting::BuonGiornoWeekday(Weekday::Wed) => Ok(BUONGIORNO_MERCOLEDI_URL.to_string()),
-            Greeting::BuonGiornoWeekday(Weekday::Thu) => Ok(BUONGIORNO_GIOVEDI_URL.to_string()),
-            Greeting::BuonGiornoWeekday(Weekday::Fri) => Ok(BUONGIORNO_VENERDI_URL.to_string()),
-            Greeting::BuonGiornoWeekday(Weekday::Sat) => Ok(BUONGIORNO_SABATO_URL.to_string()),
-            Greeting::Weekend => Ok(WEEKEND_URL.to_string()),
-            Greeting::BuonPranzo => Ok(BUON_PRANZO_URL.to_string()),
-            Greeting::BuonaNotte => Ok(BUONA_NOTTE_URL.to_string()),
-            Greeting::BuonaSerata => Ok(BUONA_SERATA_URL.to_string()),
-            Greeting::BuonaCena => Ok(BUONA_CENA_URL.to_string()),
+            Greeting::BuonGiorno => Ok(BUONGIORNO_PATH.to_string()),
+            Greeting::BuonGiornoWeekday(Weekday::Sun) => Ok(BUONGIORNO_DOMENICA_PATH.to_string()),
+            Greeting::BuonGiornoWeekday(Weekday::Mon) => Ok(BUONGIORNO_LUNEDI_PATH.to_string()),
+            Greeting::BuonGiornoWeekday(Weekday::Tue) => Ok(BUONGIORNO_MARTEDI_PATH.to_string()),
+            Greeting::BuonGiornoWeekday(Weekday::Wed) => Ok(BUONGIORNO_MERCOLEDI_PATH.to_string()),
+            Greeting::BuonGiornoWeekday(Weekday::Thu) => Ok(BUONGIORNO_GIOVEDI_PATH.to_string()),
+            Greeting::BuonGiornoWeekday(Weekday::Fri) => Ok(BUONGIORNO_VENERDI_PATH.to_string()),
+            Greeting::BuonGiornoWeekday(Weekday::Sat) => Ok(BUONGIORNO_SABATO_PATH.to_string()),
+            Greeting::Weekend => Ok(WEEKEND_PATH.to_string()),
+            Greeting::BuonPranzo => Ok(BUON_PRANZO_PATH.to_string()),
+            Greeting::BuonaNotte => Ok(BUONA_NOTTE_PATH.to_string()),
+            Greeting::BuonaSerata => Ok(BUONA_SERATA_PATH.to_string()),
+            Greeting::BuonaCena => Ok(BUONA_CENA_PATH.to_string()),
             _ => Err(ScrapeError::UnsupportedGreeting),
         }
     }
-}
 
-#[async_trait]
-impl Scrape for BuongiornoImmagini {
-    async fn scrape(&self, greeting: Greeting) -> ScrapeResult<Vec<Url>> {
-        let url = Self::get_url(greeting)?;
-        debug!("scraping greeting of kind {:?} at {}", greeting, url);
-        // send request
-        let body = reqwest::get(&url).await?.text().await?;
-        debug!("got body of length {}", body.len());
-        trace!("body: {}", body);
+    /// Parse the html page of the provider, located at `page_url`, and return the urls of the images found in it.
+    ///
+    /// This can be used to scrape pages which have been fetched by other means.
+    pub fn parse_html(html: &str, page_url: &Url) -> ScrapeResult<Vec<Url>> {
+        Self::parse_images(html, page_url)
+            .map(|images| images.into_iter().map(|image| image.url).collect())
+    }
+
+    /// Parse the html page of the provider, located at `page_url`, and return the images found in it.
+    ///
+    /// This is what [`Scrape::scrape_images`] runs on the fetched page.
+    pub fn parse_images(html: &str, page_url: &Url) -> ScrapeResult<Vec<ScrapedImage>> {
         // parse document
-        let document = Html::parse_document(&body);
+        let document = Html::parse_document(html);
         debug!("html document parsed");
         // search for entry content selector
-        let main_selector = Selector::parse(r#"main"#).unwrap();
+        let main_selector = Selector::parse(MAIN_SELECTOR).unwrap();
         let mut main_elements = document.select(&main_selector);
         let main = main_elements.next();
         let Some(main) = main else {
             error!("main is none");
-            return Err(ScrapeError::UnexpectedHtml(
-                "could not find a div with class entry-content".to_string(),
-            ));
+            return Err(ScrapeError::UnexpectedHtml {
+                provider: PROVIDER_NAME.to_string(),
+                url: Box::new(page_url.clone()),
+                selector: MAIN_SELECTOR.to_string(),
+            });
         };
 
         debug!("selecting images in thumbnail");
-        let mut urls = Vec::new();
+        let mut images = Vec::new();
         let img_selector = Selector::parse("img").unwrap();
-        let images = main.select(&img_selector);
-        for image in images {
-            if let Some(Ok(url)) = image
-                .value()
-                .attr("data-src")
-                .filter(|s| s.starts_with("http") || s.starts_with("https"))
-                .map(Url::from_str)
+        let elements = main.select(&img_selector);
+        for element in elements {
+            if let Some(image) =
+                ScrapedImage::from_element(element, SourcePolicy::LazyOnly, page_url, PROVIDER_NAME)
             {
-                debug!("found image with url {}", url);
-                urls.push(url)
+                debug!("found image with url {}", image.url);
+                images.push(image)
             }
         }
 
-        if urls.is_empty() {
-            error!("urls is empty");
-            return Err(ScrapeError::NoImages);
+        if images.is_empty() {
+            error!("images is empty");
+            return Err(ScrapeError::no_images(PROVIDER_NAME, page_url));
         }
-        Ok(urls)
+        Ok(images)
+    }
+}
+
+#[async_trait]
+impl Scrape for BuongiornoImmagini {
+    async fn scrape_images(&self, greeting: Greeting) -> ScrapeResult<Vec<ScrapedImage>> {
+        let url = self.get_url(greeting)?;
+        debug!("scraping greeting of kind {:?} at {}", greeting, url);
+        self.pagination
+            .crawl(&self.http, url, Self::parse_images)
+            .await
+    }
+
+    fn supports(&self, greeting: Greeting) -> bool {
+        Self::get_path(greeting).is_ok()
     }
 }
 
@@ -115,8 +202,141 @@ impl Scrape for BuongiornoImmagini {
 mod test {
 
     use super::*;
+    use crate::test_server::TestServer;
+
+    const IMAGES_FIXTURE: &str =
+        include_str!("../../tests/fixtures/buongiorno_immagini/article.html");
+    const NO_IMAGES_FIXTURE: &str =
+        include_str!("../../tests/fixtures/buongiorno_immagini/no_images.html");
+    const NO_CONTENT_FIXTURE: &str = include_str!("../../tests/fixtures/no_content.html");
+
+    fn page_url() -> Url {
+        Url::parse("https://buongiornoimmagini.it/immagini-buongiorno/").unwrap()
+    }
+
+    #[test]
+    fn should_get_url_with_default_base_url() {
+        assert_eq!(
+            BuongiornoImmagini::default()
+                .get_url(Greeting::BuonGiorno)
+                .unwrap()
+                .as_str(),
+            "https://buongiornoimmagini.it/immagini-buongiorno/"
+        );
+    }
+
+    #[test]
+    fn should_get_url_with_custom_base_url() {
+        let provider = BuongiornoImmagini::default()
+            .with_base_url(Url::parse("http://localhost:8080/mirror/").unwrap());
+        assert_eq!(
+            provider.get_url(Greeting::BuonGiorno).unwrap().as_str(),
+            "http://localhost:8080/mirror/immagini-buongiorno/"
+        );
+    }
+
+    #[test]
+    fn should_tell_supported_greetings() {
+        let provider = BuongiornoImmagini::default();
+        assert_eq!(
+            provider.supported_greetings().collect::<Vec<_>>(),
+            vec![
+                Greeting::BuonGiorno,
+                Greeting::BuonGiornoWeekday(Weekday::Mon),
+                Greeting::BuonGiornoWeekday(Weekday::Tue),
+                Greeting::BuonGiornoWeekday(Weekday::Wed),
+                Greeting::BuonGiornoWeekday(Weekday::Thu),
+                Greeting::BuonGiornoWeekday(Weekday::Fri),
+                Greeting::BuonGiornoWeekday(Weekday::Sat),
+                Greeting::BuonGiornoWeekday(Weekday::Sun),
+                Greeting::Weekend,
+                Greeting::BuonPranzo,
+                Greeting::BuonaNotte,
+                Greeting::BuonaSerata,
+                Greeting::BuonaCena,
+            ]
+        );
+    }
+
+    #[test]
+    fn should_parse_images_from_html() {
+        assert_eq!(
+            BuongiornoImmagini::parse_html(IMAGES_FIXTURE, &page_url()).unwrap(),
+            vec![
+                Url::parse("https://buongiornoimmagini.it/wp-content/uploads/2023/11/buongiorno-colazione.jpg").unwrap(),
+                Url::parse("https://buongiornoimmagini.it/wp-content/uploads/2023/11/buongiorno-paesaggio.webp").unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_parse_images_metadata_from_html() {
+        let images = BuongiornoImmagini::parse_images(IMAGES_FIXTURE, &page_url()).unwrap();
+        assert_eq!(
+            images[0],
+            ScrapedImage {
+                url: Url::parse("https://buongiornoimmagini.it/wp-content/uploads/2023/11/buongiorno-colazione.jpg").unwrap(),
+                alt: Some("Buongiorno colazione".to_string()),
+                width: Some(720),
+                height: Some(720),
+                srcset: vec![],
+                page_url: page_url(),
+                provider: "BuongiornoImmagini".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn should_not_parse_html_without_main() {
+        assert!(matches!(
+            BuongiornoImmagini::parse_html(NO_CONTENT_FIXTURE, &page_url()),
+            Err(ScrapeError::UnexpectedHtml { .. })
+        ));
+    }
+
+    #[test]
+    fn should_not_parse_html_without_images() {
+        assert_eq!(
+            BuongiornoImmagini::parse_html(NO_IMAGES_FIXTURE, &page_url()).unwrap_err(),
+            ScrapeError::no_images(PROVIDER_NAME, &page_url())
+        );
+    }
+
+    #[tokio::test]
+    async fn should_scrape_images_from_base_url() {
+        let server = TestServer::serve(IMAGES_FIXTURE).await;
+        let urls = BuongiornoImmagini::default()
+            .with_base_url(server.url())
+            .scrape(Greeting::BuonGiorno)
+            .await
+            .unwrap();
+        assert_eq!(urls.len(), 2);
+        assert_eq!(server.requests()[0].path, "/immagini-buongiorno/");
+    }
+
+    #[tokio::test]
+    async fn should_return_http_error_if_site_is_unreachable() {
+        let provider =
+            BuongiornoImmagini::default().with_base_url(Url::parse("http://127.0.0.1:1/").unwrap());
+        assert!(matches!(
+            provider.scrape(Greeting::BuonGiorno).await,
+            Err(ScrapeError::Connect { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn should_not_scrape_unsupported_greeting() {
+        assert_eq!(
+            BuongiornoImmagini::default()
+                .scrape(Greeting::Natale)
+                .await
+                .unwrap_err(),
+            ScrapeError::UnsupportedGreeting
+        );
+    }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn should_get_goodmorning_images() {
         crate::test_log();
         assert!(
@@ -129,6 +349,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn test_should_get_buon_pranzo() {
         crate::test_log();
         assert!(
@@ -141,6 +362,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn test_should_get_buona_notte() {
         crate::test_log();
         assert!(
@@ -153,6 +375,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn test_should_get_buona_serata() {
         crate::test_log();
         assert!(
@@ -165,6 +388,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn test_should_get_buona_cena() {
         crate::test_log();
         assert!(
@@ -177,6 +401,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn test_should_get_weekend() {
         crate::test_log();
         assert!(
@@ -189,6 +414,7 @@ mod test {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "live-tests"), ignore = "requires network access")]
     async fn should_get_weekday_images() {
         crate::test_log();
         assert!(
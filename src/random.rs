@@ -0,0 +1,167 @@
+//! # Random
+//!
+//! This module exposes the extension of the [`Scrape`] trait to pick random images
+
+use async_trait::async_trait;
+use rand::Rng;
+use rand::seq::IndexedRandom;
+use url::Url;
+
+use super::{Greeting, Scrape, ScrapeError, ScrapeResult};
+
+/// Extension of [`Scrape`] to pick random images among the scraped ones.
+///
+/// It's implemented for every scraper. The `_with` variants accept the random number generator to use,
+/// so that the picked images can be reproduced, e.g. using a seeded [`rand::rngs::StdRng`].
+///
+/// ```rust,no_run
+/// use buongiornissimo_rs::{BuongiornissimoCaffe, Greeting, ScrapeRandom};
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     let url = BuongiornissimoCaffe::default().scrape_random(Greeting::BuonGiorno).await?;
+///     let urls = BuongiornissimoCaffe::default().scrape_n(Greeting::BuonaNotte, 3).await?;
+///     Ok(())
+/// }
+/// ```
+#[async_trait]
+pub trait ScrapeRandom: Scrape {
+    /// Scrape for a certain kind of greeting and return the url of a random image.
+    ///
+    /// Returns [`ScrapeError::NoImages`] if no image was found.
+    async fn scrape_random(&self, greeting: Greeting) -> ScrapeResult<Url> {
+        let urls = self.scrape(greeting).await?;
+        urls.choose(&mut rand::rng())
+            .cloned()
+            .ok_or(ScrapeError::no_images_found())
+    }
+
+    /// Scrape for a certain kind of greeting and return the url of an image picked with `rng`.
+    ///
+    /// Returns [`ScrapeError::NoImages`] if no image was found.
+    async fn scrape_random_with<R>(&self, greeting: Greeting, rng: &mut R) -> ScrapeResult<Url>
+    where
+        R: Rng + Send + ?Sized,
+    {
+        let urls = self.scrape(greeting).await?;
+        urls.choose(rng)
+            .cloned()
+            .ok_or(ScrapeError::no_images_found())
+    }
+
+    /// Scrape for a certain kind of greeting and return the urls of `n` different random images, in random order.
+    ///
+    /// If less than `n` images were found, all of them are returned.
+    async fn scrape_n(&self, greeting: Greeting, n: usize) -> ScrapeResult<Vec<Url>> {
+        let urls = self.scrape(greeting).await?;
+        Ok(urls.choose_multiple(&mut rand::rng(), n).cloned().collect())
+    }
+
+    /// Scrape for a certain kind of greeting and return the urls of `n` different images picked with `rng`, in random order.
+    ///
+    /// If less than `n` images were found, all of them are returned.
+    async fn scrape_n_with<R>(
+        &self,
+        greeting: Greeting,
+        n: usize,
+        rng: &mut R,
+    ) -> ScrapeResult<Vec<Url>>
+    where
+        R: Rng + Send + ?Sized,
+    {
+        let urls = self.scrape(greeting).await?;
+        Ok(urls.choose_multiple(rng, n).cloned().collect())
+    }
+}
+
+impl<S> ScrapeRandom for S where S: Scrape + ?Sized {}
+
+#[cfg(test)]
+mod test {
+
+    use std::collections::HashSet;
+
+    use pretty_assertions::assert_eq;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    use super::*;
+    use crate::mock::MockProvider;
+
+    const URLS: &[&str] = &[
+        "https://a.it/1.jpg",
+        "https://a.it/2.jpg",
+        "https://a.it/3.jpg",
+        "https://a.it/4.jpg",
+        "https://a.it/5.jpg",
+    ];
+
+    fn provider() -> MockProvider {
+        MockProvider::default()
+            .with_urls(Greeting::BuonGiorno, URLS)
+            .with_urls(Greeting::BuonaNotte, &[])
+    }
+
+    #[tokio::test]
+    async fn should_scrape_random_image() {
+        let url = provider()
+            .scrape_random(Greeting::BuonGiorno)
+            .await
+            .unwrap();
+        assert!(URLS.contains(&url.as_str()));
+        assert_eq!(
+            provider()
+                .scrape_random(Greeting::BuonaNotte)
+                .await
+                .unwrap_err(),
+            ScrapeError::no_images_found()
+        );
+    }
+
+    #[tokio::test]
+    async fn should_reproduce_random_picks_with_seeded_rng() {
+        let scraper = provider();
+        let first = scraper
+            .scrape_random_with(Greeting::BuonGiorno, &mut StdRng::seed_from_u64(42))
+            .await
+            .unwrap();
+        let second = scraper
+            .scrape_random_with(Greeting::BuonGiorno, &mut StdRng::seed_from_u64(42))
+            .await
+            .unwrap();
+        assert_eq!(first, second);
+
+        let first = scraper
+            .scrape_n_with(Greeting::BuonGiorno, 3, &mut StdRng::seed_from_u64(42))
+            .await
+            .unwrap();
+        let second = scraper
+            .scrape_n_with(Greeting::BuonGiorno, 3, &mut StdRng::seed_from_u64(42))
+            .await
+            .unwrap();
+        assert_eq!(first, second);
+    }
+
+    #[tokio::test]
+    async fn should_scrape_n_different_images() {
+        let urls = provider().scrape_n(Greeting::BuonGiorno, 3).await.unwrap();
+        assert_eq!(urls.len(), 3);
+        assert_eq!(urls.iter().collect::<HashSet<_>>().len(), 3);
+
+        let urls = provider().scrape_n(Greeting::BuonGiorno, 10).await.unwrap();
+        assert_eq!(urls.len(), URLS.len());
+        assert!(
+            provider()
+                .scrape_n(Greeting::BuonaNotte, 3)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn should_scrape_random_image_from_boxed_scraper() {
+        let scraper: Box<dyn Scrape> = Box::new(provider());
+        assert!(scraper.scrape_random(Greeting::BuonGiorno).await.is_ok());
+    }
+}
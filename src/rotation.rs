@@ -0,0 +1,335 @@
+//! # Rotation
+//!
+//! This module exposes the rotation, which delivers the scraped images without repeating them to the same recipient
+
+mod file;
+mod memory;
+
+use std::collections::{HashSet, VecDeque};
+
+use async_trait::async_trait;
+use rand::rngs::StdRng;
+use rand::seq::IndexedRandom;
+use rand::{Rng, SeedableRng};
+use url::Url;
+
+pub use self::file::FileHistory;
+pub use self::memory::MemoryHistory;
+use super::{Greeting, Scrape, ScrapeError, ScrapeResult, ScrapedImage};
+
+/// Default maximum amount of images remembered for each recipient by the history stores
+const DEFAULT_MAX_LEN: usize = 1024;
+
+/// A store of the images already delivered to each recipient, used by [`Rotation`]
+#[async_trait]
+pub trait HistoryStore: Send + Sync {
+    /// Get the urls of the images already delivered to `recipient`
+    async fn delivered(&self, recipient: &str) -> ScrapeResult<HashSet<Url>>;
+
+    /// Record that `url` has been delivered to `recipient`
+    async fn record(&self, recipient: &str, url: &Url) -> ScrapeResult<()>;
+
+    /// Forget that `urls` have been delivered to `recipient`, so that they can be delivered again
+    async fn forget(&self, recipient: &str, urls: &[Url]) -> ScrapeResult<()>;
+}
+
+/// Delivers the images scraped by the wrapped provider without repeating them to the same recipient.
+///
+/// Each recipient is identified by a key (e.g. the id of a chat), and the images delivered to it are tracked by the [`HistoryStore`].
+/// The next image is picked at random among the ones which have not been delivered to the recipient yet;
+/// once all the images scraped for the greeting have been delivered, their history is reset and the rotation starts over.
+///
+/// ```rust,no_run
+/// use buongiornissimo_rs::{BuongiornissimoCaffe, FileHistory, Greeting, Rotation};
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     let rotation = Rotation::new(
+///         BuongiornissimoCaffe::default(),
+///         FileHistory::new("/var/lib/buongiornissimo/history"),
+///     );
+///     let image = rotation.next("group-42", Greeting::BuonGiorno).await?;
+///     Ok(())
+/// }
+/// ```
+pub struct Rotation<S, H = MemoryHistory> {
+    provider: S,
+    history: H,
+    /// Serializes the deliveries, so that concurrent deliveries to the same recipient don't pick the same image
+    lock: tokio::sync::Mutex<()>,
+}
+
+impl<S> Rotation<S, MemoryHistory>
+where
+    S: Scrape,
+{
+    /// Create a new [`Rotation`] over `provider`, which keeps the history in memory
+    pub fn in_memory(provider: S) -> Self {
+        Self::new(provider, MemoryHistory::default())
+    }
+}
+
+impl<S, H> Rotation<S, H>
+where
+    S: Scrape,
+    H: HistoryStore,
+{
+    /// Create a new [`Rotation`] over `provider`, which keeps the history in `history`
+    pub fn new(provider: S, history: H) -> Self {
+        Self {
+            provider,
+            history,
+            lock: tokio::sync::Mutex::default(),
+        }
+    }
+
+    /// Get the wrapped provider
+    pub fn provider(&self) -> &S {
+        &self.provider
+    }
+
+    /// Get the history store
+    pub fn history(&self) -> &H {
+        &self.history
+    }
+
+    /// Get a random image for `greeting` which has not been delivered to `recipient` yet, and record it as delivered.
+    ///
+    /// Returns [`ScrapeError::NoImages`] if the provider didn't find any image.
+    pub async fn next(&self, recipient: &str, greeting: Greeting) -> ScrapeResult<ScrapedImage> {
+        // the thread rng can't be kept across awaits, so a sendable one is seeded from it
+        let mut rng = StdRng::from_rng(&mut rand::rng());
+        self.next_with(recipient, greeting, &mut rng).await
+    }
+
+    /// Get an image for `greeting` which has not been delivered to `recipient` yet, picked with `rng`, and record it as delivered.
+    ///
+    /// Returns [`ScrapeError::NoImages`] if the provider didn't find any image.
+    pub async fn next_with<R>(
+        &self,
+        recipient: &str,
+        greeting: Greeting,
+        rng: &mut R,
+    ) -> ScrapeResult<ScrapedImage>
+    where
+        R: Rng + Send + ?Sized,
+    {
+        let images = self.provider.scrape_images(greeting).await?;
+        let _lock = self.lock.lock().await;
+        let undelivered = self.undelivered(recipient, &images).await?;
+        let image = undelivered
+            .choose(rng)
+            .copied()
+            .ok_or(ScrapeError::no_images_found())?
+            .clone();
+        self.history.record(recipient, &image.url).await?;
+
+        Ok(image)
+    }
+
+    /// Get the images which have not been delivered to `recipient` yet.
+    ///
+    /// If all of them have been delivered, their history is reset and all the images are returned.
+    async fn undelivered<'a>(
+        &self,
+        recipient: &str,
+        images: &'a [ScrapedImage],
+    ) -> ScrapeResult<Vec<&'a ScrapedImage>> {
+        let delivered = self.history.delivered(recipient).await?;
+        let undelivered: Vec<&ScrapedImage> = images
+            .iter()
+            .filter(|image| !delivered.contains(&image.url))
+            .collect();
+        if !undelivered.is_empty() {
+            return Ok(undelivered);
+        }
+
+        debug!("all the images have been delivered to {recipient}; starting over");
+        let urls: Vec<Url> = images.iter().map(|image| image.url.clone()).collect();
+        self.history.forget(recipient, &urls).await?;
+        Ok(images.iter().collect())
+    }
+}
+
+/// The images delivered to a recipient, oldest first
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+struct Deliveries(VecDeque<Url>);
+
+impl Deliveries {
+    /// Record that `url` has been delivered, forgetting the oldest deliveries beyond `max_len`.
+    ///
+    /// Returns whether the deliveries changed
+    fn record(&mut self, url: &Url, max_len: usize) -> bool {
+        if self.0.contains(url) {
+            return false;
+        }
+        self.0.push_back(url.clone());
+        while self.0.len() > max_len {
+            self.0.pop_front();
+        }
+        true
+    }
+
+    /// Forget that `urls` have been delivered.
+    ///
+    /// Returns whether the deliveries changed
+    fn forget(&mut self, urls: &[Url]) -> bool {
+        let len = self.0.len();
+        self.0.retain(|url| !urls.contains(url));
+        self.0.len() != len
+    }
+
+    fn urls(&self) -> HashSet<Url> {
+        self.0.iter().cloned().collect()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn iter(&self) -> impl Iterator<Item = &Url> {
+        self.0.iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    use super::*;
+    use crate::mock::MockProvider;
+
+    const URLS: &[&str] = &[
+        "https://a.it/1.jpg",
+        "https://a.it/2.jpg",
+        "https://a.it/3.jpg",
+    ];
+
+    fn rotation() -> Rotation<MockProvider> {
+        Rotation::in_memory(
+            MockProvider::default()
+                .with_urls(Greeting::BuonGiorno, URLS)
+                .with_urls(Greeting::BuonaNotte, &["https://a.it/4.jpg"])
+                .with_urls(Greeting::BuonaSerata, &[]),
+        )
+    }
+
+    async fn deliver(rotation: &Rotation<MockProvider>, recipient: &str, n: usize) -> Vec<String> {
+        let mut urls = Vec::new();
+        for _ in 0..n {
+            let image = rotation
+                .next(recipient, Greeting::BuonGiorno)
+                .await
+                .unwrap();
+            urls.push(image.url.to_string());
+        }
+        urls
+    }
+
+    #[tokio::test]
+    async fn should_not_repeat_images_until_exhausted() {
+        let rotation = rotation();
+        let mut delivered = deliver(&rotation, "group", 3).await;
+        delivered.sort();
+        assert_eq!(delivered, URLS);
+
+        // pool exhausted: starts over
+        let mut delivered = deliver(&rotation, "group", 3).await;
+        delivered.sort();
+        assert_eq!(delivered, URLS);
+    }
+
+    #[tokio::test]
+    async fn should_track_recipients_separately() {
+        let rotation = rotation();
+        deliver(&rotation, "first", 2).await;
+        assert_eq!(
+            rotation.history().delivered("first").await.unwrap().len(),
+            2
+        );
+        assert!(
+            rotation
+                .history()
+                .delivered("second")
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        let mut delivered = deliver(&rotation, "second", 3).await;
+        delivered.sort();
+        assert_eq!(delivered, URLS);
+    }
+
+    #[tokio::test]
+    async fn should_reset_only_exhausted_greeting() {
+        let rotation = rotation();
+        deliver(&rotation, "group", 1).await;
+        for _ in 0..2 {
+            assert_eq!(
+                rotation
+                    .next("group", Greeting::BuonaNotte)
+                    .await
+                    .unwrap()
+                    .url
+                    .as_str(),
+                "https://a.it/4.jpg"
+            );
+        }
+        // the buongiorno history survives the buonanotte reset
+        assert_eq!(
+            rotation.history().delivered("group").await.unwrap().len(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn should_reproduce_deliveries_with_seeded_rng() {
+        let mut picks = Vec::new();
+        for _ in 0..2 {
+            let rotation = rotation();
+            let mut rng = StdRng::seed_from_u64(7);
+            let mut urls = Vec::new();
+            for _ in 0..3 {
+                urls.push(
+                    rotation
+                        .next_with("group", Greeting::BuonGiorno, &mut rng)
+                        .await
+                        .unwrap()
+                        .url,
+                );
+            }
+            picks.push(urls);
+        }
+        assert_eq!(picks[0], picks[1]);
+    }
+
+    #[test]
+    fn should_record_deliveries_up_to_max_len() {
+        let url = |n: usize| Url::parse(&format!("https://a.it/{n}.jpg")).unwrap();
+        let mut deliveries = Deliveries::default();
+        assert!(deliveries.record(&url(1), 2));
+        assert!(!deliveries.record(&url(1), 2));
+        assert!(deliveries.record(&url(2), 2));
+        assert!(deliveries.record(&url(3), 2));
+        assert_eq!(deliveries.urls(), HashSet::from([url(2), url(3)]));
+
+        assert!(deliveries.forget(&[url(2), url(4)]));
+        assert!(!deliveries.forget(&[url(4)]));
+        assert_eq!(deliveries.urls(), HashSet::from([url(3)]));
+    }
+
+    #[tokio::test]
+    async fn should_fail_without_images() {
+        assert_eq!(
+            rotation()
+                .next("group", Greeting::BuonaSerata)
+                .await
+                .unwrap_err(),
+            ScrapeError::no_images_found()
+        );
+    }
+}
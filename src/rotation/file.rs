@@ -0,0 +1,258 @@
+//! # File history
+//!
+//! A history store which persists the delivered images in a file
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use url::Url;
+
+use super::{DEFAULT_MAX_LEN, Deliveries, HistoryStore, ScrapeError, ScrapeResult};
+
+const HEADER: &str = "# buongiornissimo-rs history v1";
+
+type History = HashMap<String, Deliveries>;
+
+/// A [`HistoryStore`] which persists the delivered images in a file, so that the history survives restarts.
+///
+/// The file is read on first access and it's rewritten on each change.
+/// Line breaks in the recipient keys are replaced with spaces.
+/// At most [`FileHistory::with_max_len`] images are remembered for each recipient; older deliveries are forgotten.
+#[derive(Debug)]
+pub struct FileHistory {
+    path: PathBuf,
+    history: tokio::sync::Mutex<Option<History>>,
+    max_len: usize,
+}
+
+impl FileHistory {
+    /// Create a new [`FileHistory`] which persists the history at `path`. The file is created if it doesn't exist.
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            history: tokio::sync::Mutex::default(),
+            max_len: DEFAULT_MAX_LEN,
+        }
+    }
+
+    /// Set the maximum amount of images remembered for each recipient. Default is 1024
+    pub fn with_max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self
+    }
+
+    /// Get the path of the history file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Get the history, reading it from the file on first access
+    async fn load<'a>(&self, history: &'a mut Option<History>) -> ScrapeResult<&'a mut History> {
+        if history.is_none() {
+            let content = match tokio::fs::read_to_string(&self.path).await {
+                Ok(content) => content,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+                Err(err) => return Err(self.error("read", err)),
+            };
+            *history = Some(decode(&content, self.max_len).ok_or_else(|| {
+                ScrapeError::History(format!("invalid history file at {}", self.path.display()))
+            })?);
+        }
+
+        Ok(history.get_or_insert_default())
+    }
+
+    /// Write `history` to the file, through a temporary file so that it's never partially written
+    async fn save(&self, history: &History) -> ScrapeResult<()> {
+        crate::fs::write_atomic(&self.path, encode(history))
+            .await
+            .map_err(|err| self.error("write", err))
+    }
+
+    fn error(&self, action: &str, err: std::io::Error) -> ScrapeError {
+        ScrapeError::History(format!("could not {action} {}: {err}", self.path.display()))
+    }
+}
+
+#[async_trait]
+impl HistoryStore for FileHistory {
+    async fn delivered(&self, recipient: &str) -> ScrapeResult<HashSet<Url>> {
+        let mut history = self.history.lock().await;
+        let history = self.load(&mut history).await?;
+        Ok(history
+            .get(&key(recipient))
+            .map(Deliveries::urls)
+            .unwrap_or_default())
+    }
+
+    async fn record(&self, recipient: &str, url: &Url) -> ScrapeResult<()> {
+        let mut history = self.history.lock().await;
+        let history = self.load(&mut history).await?;
+        if history
+            .entry(key(recipient))
+            .or_default()
+            .record(url, self.max_len)
+        {
+            self.save(history).await?;
+        }
+        Ok(())
+    }
+
+    async fn forget(&self, recipient: &str, urls: &[Url]) -> ScrapeResult<()> {
+        let mut history = self.history.lock().await;
+        let history = self.load(&mut history).await?;
+        let recipient = key(recipient);
+        if let Some(delivered) = history.get_mut(&recipient)
+            && delivered.forget(urls)
+        {
+            if delivered.is_empty() {
+                history.remove(&recipient);
+            }
+            self.save(history).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Get the key of `recipient` in the history file, which can't contain line breaks
+fn key(recipient: &str) -> String {
+    recipient.replace(['\r', '\n'], " ")
+}
+
+/// Encode the history: a header and a line for each delivered image, with its url and the recipient.
+///
+/// Recipients are sorted, while the images of each recipient are kept in delivery order, oldest first
+fn encode(history: &History) -> String {
+    let mut recipients: Vec<&String> = history.keys().collect();
+    recipients.sort();
+    let mut content = format!("{HEADER}\n");
+    for recipient in recipients {
+        for url in history[recipient].iter() {
+            content.push_str(&format!("{url}\t{recipient}\n"));
+        }
+    }
+    content
+}
+
+/// Decode the history, keeping the latest `max_len` images for each recipient; an empty content is an empty history
+fn decode(content: &str, max_len: usize) -> Option<History> {
+    let mut lines = content.lines();
+    let mut history = History::new();
+    match lines.next() {
+        None => return Some(history),
+        Some(HEADER) => {}
+        Some(_) => return None,
+    }
+    for line in lines.filter(|line| !line.trim().is_empty()) {
+        let (url, recipient) = line.split_once('\t')?;
+        history
+            .entry(recipient.to_string())
+            .or_default()
+            .record(&Url::parse(url).ok()?, max_len);
+    }
+
+    Some(history)
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn url(n: usize) -> Url {
+        Url::parse(&format!("https://a.it/{n}.jpg")).unwrap()
+    }
+
+    #[test]
+    fn should_encode_and_decode_history() {
+        let mut history = History::new();
+        let group = history.entry("group\t1".to_string()).or_default();
+        group.record(&url(2), usize::MAX);
+        group.record(&url(1), usize::MAX);
+        history
+            .entry("other".to_string())
+            .or_default()
+            .record(&url(1), usize::MAX);
+        let content = encode(&history);
+        assert_eq!(
+            content,
+            "# buongiornissimo-rs history v1\nhttps://a.it/2.jpg\tgroup\t1\nhttps://a.it/1.jpg\tgroup\t1\nhttps://a.it/1.jpg\tother\n"
+        );
+        assert_eq!(decode(&content, usize::MAX), Some(history));
+        assert_eq!(
+            decode(&content, 1).unwrap()["group\t1"].urls(),
+            HashSet::from([url(1)])
+        );
+        assert_eq!(decode("", usize::MAX), Some(History::new()));
+        assert_eq!(decode("garbage", usize::MAX), None);
+    }
+
+    #[tokio::test]
+    async fn should_persist_history() {
+        let directory = TempDir::new().unwrap();
+        let path = directory.path().join("history/delivered.txt");
+        let store = FileHistory::new(&path);
+        assert!(store.delivered("group").await.unwrap().is_empty());
+        store.record("group", &url(1)).await.unwrap();
+        store.record("group", &url(2)).await.unwrap();
+        store.record("multi\nline", &url(3)).await.unwrap();
+
+        // a new instance, e.g. after a restart, reads the persisted history
+        let store = FileHistory::new(&path);
+        assert_eq!(
+            store.delivered("group").await.unwrap(),
+            HashSet::from([url(1), url(2)])
+        );
+        assert_eq!(
+            store.delivered("multi\nline").await.unwrap(),
+            HashSet::from([url(3)])
+        );
+
+        store.forget("group", &[url(1), url(2)]).await.unwrap();
+        assert!(
+            FileHistory::new(&path)
+                .delivered("group")
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn should_forget_oldest_deliveries_beyond_max_len() {
+        let directory = TempDir::new().unwrap();
+        let path = directory.path().join("history.txt");
+        let store = FileHistory::new(&path).with_max_len(2);
+        for n in 1..=3 {
+            store.record("group", &url(n)).await.unwrap();
+        }
+
+        // the oldest delivery is forgotten in the file too
+        let store = FileHistory::new(&path).with_max_len(2);
+        assert_eq!(
+            store.delivered("group").await.unwrap(),
+            HashSet::from([url(2), url(3)])
+        );
+        store.record("group", &url(4)).await.unwrap();
+        assert_eq!(
+            FileHistory::new(&path).delivered("group").await.unwrap(),
+            HashSet::from([url(3), url(4)])
+        );
+    }
+
+    #[tokio::test]
+    async fn should_fail_on_invalid_history_file() {
+        let directory = TempDir::new().unwrap();
+        let path = directory.path().join("history.txt");
+        tokio::fs::write(&path, "not a history").await.unwrap();
+
+        assert!(matches!(
+            FileHistory::new(&path).delivered("group").await,
+            Err(ScrapeError::History(_))
+        ));
+    }
+}
@@ -0,0 +1,101 @@
+//! # Memory history
+//!
+//! A history store which keeps the delivered images in memory
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use url::Url;
+
+use super::{DEFAULT_MAX_LEN, Deliveries, HistoryStore, ScrapeResult};
+
+/// A [`HistoryStore`] which keeps the delivered images in memory, so that the history is lost on restart.
+///
+/// At most [`MemoryHistory::with_max_len`] images are remembered for each recipient; older deliveries are forgotten.
+#[derive(Debug)]
+pub struct MemoryHistory {
+    delivered: Mutex<HashMap<String, Deliveries>>,
+    max_len: usize,
+}
+
+impl Default for MemoryHistory {
+    fn default() -> Self {
+        Self {
+            delivered: Mutex::default(),
+            max_len: DEFAULT_MAX_LEN,
+        }
+    }
+}
+
+impl MemoryHistory {
+    /// Set the maximum amount of images remembered for each recipient. Default is 1024
+    pub fn with_max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self
+    }
+
+    fn entries(&self) -> std::sync::MutexGuard<'_, HashMap<String, Deliveries>> {
+        self.delivered.lock().expect("history lock poisoned")
+    }
+}
+
+#[async_trait]
+impl HistoryStore for MemoryHistory {
+    async fn delivered(&self, recipient: &str) -> ScrapeResult<HashSet<Url>> {
+        Ok(self
+            .entries()
+            .get(recipient)
+            .map(Deliveries::urls)
+            .unwrap_or_default())
+    }
+
+    async fn record(&self, recipient: &str, url: &Url) -> ScrapeResult<()> {
+        self.entries()
+            .entry(recipient.to_string())
+            .or_default()
+            .record(url, self.max_len);
+        Ok(())
+    }
+
+    async fn forget(&self, recipient: &str, urls: &[Url]) -> ScrapeResult<()> {
+        let mut delivered = self.entries();
+        if let Some(history) = delivered.get_mut(recipient) {
+            history.forget(urls);
+            if history.is_empty() {
+                delivered.remove(recipient);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn url(n: usize) -> Url {
+        Url::parse(&format!("https://a.it/{n}.jpg")).unwrap()
+    }
+
+    #[tokio::test]
+    async fn should_forget_oldest_deliveries_beyond_max_len() {
+        let store = MemoryHistory::default().with_max_len(2);
+        for n in 1..=3 {
+            store.record("group", &url(n)).await.unwrap();
+        }
+        store.record("other", &url(1)).await.unwrap();
+
+        assert_eq!(
+            store.delivered("group").await.unwrap(),
+            HashSet::from([url(2), url(3)])
+        );
+        assert_eq!(
+            store.delivered("other").await.unwrap(),
+            HashSet::from([url(1)])
+        );
+    }
+}
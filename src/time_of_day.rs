@@ -0,0 +1,167 @@
+//! # Time of day
+//!
+//! This module exposes the time windows used to pick the greeting for a moment of the day
+
+use chrono::NaiveTime;
+
+use super::Greeting;
+
+/// A window of the day, from `start` (included) to `end` (excluded).
+///
+/// If `end` comes before `start` the window wraps around midnight, while if they're equal the window is empty.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+struct TimeWindow {
+    start: NaiveTime,
+    end: NaiveTime,
+}
+
+impl TimeWindow {
+    fn new(start: NaiveTime, end: NaiveTime) -> Self {
+        Self { start, end }
+    }
+
+    fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            self.start <= time || time < self.end
+        }
+    }
+}
+
+/// The windows of the day in which the time of day greetings are returned by [`crate::greeting_of_the_moment`].
+///
+/// Out of these windows it's morning, and the greeting for the day is returned.
+/// The windows are checked in order (pranzo, pomeriggio, serata, cena and notte), so the first one containing the time wins.
+/// Each window includes its start and excludes its end; a window ending before its start wraps around midnight,
+/// while a window ending at its start is empty, which disables the greeting.
+///
+/// The default windows are:
+///
+/// - pranzo: 12:00 - 14:00
+/// - pomeriggio: 14:00 - 18:00
+/// - serata: 18:00 - 20:00
+/// - cena: 20:00 - 22:00
+/// - notte: 22:00 - 05:00
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct TimeWindows {
+    pranzo: TimeWindow,
+    pomeriggio: TimeWindow,
+    serata: TimeWindow,
+    cena: TimeWindow,
+    notte: TimeWindow,
+}
+
+impl Default for TimeWindows {
+    fn default() -> Self {
+        Self {
+            pranzo: TimeWindow::new(hour(12), hour(14)),
+            pomeriggio: TimeWindow::new(hour(14), hour(18)),
+            serata: TimeWindow::new(hour(18), hour(20)),
+            cena: TimeWindow::new(hour(20), hour(22)),
+            notte: TimeWindow::new(hour(22), hour(5)),
+        }
+    }
+}
+
+impl TimeWindows {
+    /// Set the window in which [`Greeting::BuonPranzo`] is returned
+    pub fn with_pranzo(mut self, start: NaiveTime, end: NaiveTime) -> Self {
+        self.pranzo = TimeWindow::new(start, end);
+        self
+    }
+
+    /// Set the window in which [`Greeting::BuonPomeriggio`] is returned
+    pub fn with_pomeriggio(mut self, start: NaiveTime, end: NaiveTime) -> Self {
+        self.pomeriggio = TimeWindow::new(start, end);
+        self
+    }
+
+    /// Set the window in which [`Greeting::BuonaSerata`] is returned
+    pub fn with_serata(mut self, start: NaiveTime, end: NaiveTime) -> Self {
+        self.serata = TimeWindow::new(start, end);
+        self
+    }
+
+    /// Set the window in which [`Greeting::BuonaCena`] is returned
+    pub fn with_cena(mut self, start: NaiveTime, end: NaiveTime) -> Self {
+        self.cena = TimeWindow::new(start, end);
+        self
+    }
+
+    /// Set the window in which [`Greeting::BuonaNotte`] is returned
+    pub fn with_notte(mut self, start: NaiveTime, end: NaiveTime) -> Self {
+        self.notte = TimeWindow::new(start, end);
+        self
+    }
+
+    /// Get the time of day greeting for `time`, or `None` if it's morning
+    pub fn greeting(&self, time: NaiveTime) -> Option<Greeting> {
+        [
+            (self.pranzo, Greeting::BuonPranzo),
+            (self.pomeriggio, Greeting::BuonPomeriggio),
+            (self.serata, Greeting::BuonaSerata),
+            (self.cena, Greeting::BuonaCena),
+            (self.notte, Greeting::BuonaNotte),
+        ]
+        .into_iter()
+        .find(|(window, _)| window.contains(time))
+        .map(|(_, greeting)| greeting)
+    }
+}
+
+fn hour(hour: u32) -> NaiveTime {
+    NaiveTime::from_hms_opt(hour, 0, 0).expect("invalid hour")
+}
+
+#[cfg(test)]
+mod test {
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn time(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn should_get_greeting_with_default_windows() {
+        let windows = TimeWindows::default();
+        assert_eq!(windows.greeting(time(5, 0)), None);
+        assert_eq!(windows.greeting(time(11, 59)), None);
+        assert_eq!(windows.greeting(time(12, 0)), Some(Greeting::BuonPranzo));
+        assert_eq!(
+            windows.greeting(time(14, 0)),
+            Some(Greeting::BuonPomeriggio)
+        );
+        assert_eq!(windows.greeting(time(18, 30)), Some(Greeting::BuonaSerata));
+        assert_eq!(windows.greeting(time(21, 59)), Some(Greeting::BuonaCena));
+        assert_eq!(windows.greeting(time(22, 0)), Some(Greeting::BuonaNotte));
+        assert_eq!(windows.greeting(time(0, 0)), Some(Greeting::BuonaNotte));
+        assert_eq!(windows.greeting(time(4, 59)), Some(Greeting::BuonaNotte));
+    }
+
+    #[test]
+    fn should_get_greeting_with_custom_windows() {
+        let windows = TimeWindows::default()
+            .with_pranzo(time(12, 30), time(14, 30))
+            .with_cena(time(20, 0), time(20, 0))
+            .with_serata(time(18, 0), time(23, 0))
+            .with_notte(time(23, 0), time(6, 0));
+        assert_eq!(windows.greeting(time(12, 15)), None);
+        assert_eq!(windows.greeting(time(14, 15)), Some(Greeting::BuonPranzo));
+        assert_eq!(windows.greeting(time(20, 30)), Some(Greeting::BuonaSerata));
+        assert_eq!(windows.greeting(time(5, 30)), Some(Greeting::BuonaNotte));
+    }
+
+    #[test]
+    fn should_check_time_window() {
+        let window = TimeWindow::new(time(22, 0), time(5, 0));
+        assert!(window.contains(time(23, 0)));
+        assert!(window.contains(time(1, 0)));
+        assert!(!window.contains(time(5, 0)));
+        assert!(!window.contains(time(12, 0)));
+        assert!(!TimeWindow::new(time(8, 0), time(8, 0)).contains(time(8, 0)));
+    }
+}